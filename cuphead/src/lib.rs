@@ -8,16 +8,15 @@ use crate::enums::Mode;
 use crate::memory::Memory;
 use crate::settings::Settings;
 use crate::util::format_seconds;
-use asr::future::retry;
-use asr::game_engine::unity::mono::Module;
 use asr::game_engine::unity::scene_manager::SceneManager;
-use asr::settings::Gui;
 use asr::timer::{
     pause_game_time, reset, resume_game_time, set_game_time, set_variable, split, start, state,
     TimerState,
 };
 use asr::{future::next_tick, print_message, Process};
 use helpers::error::SimpleError;
+use helpers::runtime::AutoSplitter;
+use helpers::variables::{self, publishing, Group};
 use helpers::watchers::unity::UnityImage;
 use std::error::Error;
 use std::rc::Rc;
@@ -26,13 +25,6 @@ use std::time::Instant;
 
 asr::async_main!(stable);
 
-const PROCESS_NAMES: [&str; 2] = [
-    // Windows
-    "Cuphead.exe",
-    // Mac
-    "Cuphead",
-];
-
 const SCENE_CUTSCENE_INTRO: &str = "scene_cutscene_intro";
 const SCENE_CUTSCENE_KING_DICE_CONTRACT: &str = "scene_cutscene_kingdice";
 const SCENE_CUTSCENE_DEVIL: &str = "scene_cutscene_devil";
@@ -55,30 +47,7 @@ struct MeasuredState {
 }
 
 async fn main() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        print_message(&panic_info.to_string());
-    }));
-
-    print_message("Hello, World!");
-
-    let mut settings = Settings::register();
-    settings.update();
-
-    loop {
-        let process = retry(|| PROCESS_NAMES.iter().find_map(|name| Process::attach(name))).await;
-
-        process
-            .until_closes(async {
-                let res = on_attach(&process, &mut settings).await;
-                if let Err(err) = res {
-                    print_message(&format!("error occuring on_attach: {}", err));
-                } else {
-                    print_message("detached from process");
-                }
-            })
-            .await;
-        next_tick().await;
-    }
+    helpers::runtime::run::<Cuphead>().await;
 }
 
 struct Cuphead<'a> {
@@ -86,45 +55,42 @@ struct Cuphead<'a> {
     measured_state: MeasuredState,
 }
 
-async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Box<dyn Error>> {
-    let mut cuphead =
-        helpers::try_load::wait_try_load_millis(|| try_load(process), Duration::from_millis(500))
-            .await;
-
-    next_tick().await;
-
-    while process.is_open() {
-        settings.update();
+impl AutoSplitter for Cuphead<'_> {
+    const PROCESS_NAMES: &'static [&'static str] = &[
+        // Windows
+        "Cuphead.exe",
+        // Mac
+        "Cuphead",
+    ];
+    const LOAD_COOLDOWN: Duration = Duration::from_millis(500);
+
+    type Settings = Settings;
+    type State<'a> = Cuphead<'a>;
+
+    async fn load<'a>(
+        process: &'a Process,
+        _process_name: &'static str,
+    ) -> Result<Cuphead<'a>, Box<dyn Error>> {
+        try_load(process).await
+    }
 
-        next_tick().await;
+    async fn tick(
+        cuphead: &mut Self::State<'_>,
+        settings: &Settings,
+    ) -> Result<(), Box<dyn Error>> {
+        tick(cuphead, settings).await
+    }
 
+    fn invalidate(cuphead: &mut Self::State<'_>) {
         cuphead.memory.invalidate();
-
-        if let Err(_err) = tick(&mut cuphead, settings).await {
-            // print_message(&format!("tick failed: {err}"));
-        }
     }
-
-    Ok(())
 }
 
 async fn try_load<'a>(process: &'a Process) -> Result<Cuphead<'a>, Box<dyn Error>> {
-    print_message("  => loading module");
-    let module =
-        Module::attach_auto_detect(process).ok_or(SimpleError::from("mono module not found"))?;
-    let module = Rc::new(module);
-    print_message(&format!(
-        "  => module loaded (detected {:?}, {:?}), loading image",
-        module.get_version(),
-        module.get_pointer_size()
-    ));
-    next_tick().await;
-
-    let image = module
-        .get_default_image(process)
-        .ok_or(SimpleError::from("default image not found"))?;
-    let unity = UnityImage::new(process, module, image);
+    print_message("  => loading module and default image");
+    let unity = UnityImage::attach_auto_detect(process)?;
     print_message("  => default image loaded, loading scene manager");
+    next_tick().await;
 
     let sm = SceneManager::attach(process)
         .ok_or(SimpleError::from("failed to attach to asr scene manager"))?;
@@ -148,17 +114,11 @@ fn split_log(condition: bool, string: &str) -> bool {
     condition
 }
 
-async fn tick<'a>(
-    cuphead: &mut Cuphead<'a>,
-    settings: &mut Settings,
-) -> Result<(), Box<dyn Error>> {
+async fn tick<'a>(cuphead: &mut Cuphead<'a>, settings: &Settings) -> Result<(), Box<dyn Error>> {
     let memory = &cuphead.memory;
     let measured_state = &mut cuphead.measured_state;
-    let scene = String::from_utf16(memory.scene.current()?.as_slice())?;
-    let previous_scene = match memory.scene.old() {
-        Some(previous_scene) => String::from_utf16(previous_scene.as_slice())?,
-        None => String::new(),
-    };
+    let scene = memory.scene.current()?;
+    let previous_scene = memory.scene.old().unwrap_or_default();
     if memory.scene.changed()? {
         measured_state.last_seen_scene = previous_scene.clone();
     };
@@ -214,31 +174,31 @@ async fn tick<'a>(
     set_variable("Star Skip Counter", counter);
 
     // For run recap component - key matters
-    // Future improvement - make these a setting so we save extra performance?
-    set_variable("scene name", &scene.to_string());
-    set_variable("loading", &format!("{:?}", !memory.done_loading.current()?));
-    set_variable(
-        "difficulty",
-        &format!("{:?}", memory.level_difficulty.current()?),
-    );
-    set_variable("scoring time", &format!("{}", memory.lsd_time.current()?));
-    set_variable("parries", &format!("{}", memory.lsd_parries.current()?));
-    set_variable(
-        "super meter",
-        &format!("{}", memory.lsd_super_meter.current()?),
-    );
-    set_variable("coins", &format!("{}", memory.lsd_coins.current()?));
-    set_variable("hits", &format!("{}", memory.lsd_hits.current()?));
-    set_variable(
-        "use coins instead of super meter",
-        &format!("{}", memory.lsd_use_coins_instead.current()?),
-    );
-    set_variable("star skip counter raw", counter_raw);
-    set_variable("is run in progress", &format!("{}", is_run_in_progress));
+    if publishing(Group::RunRecap) {
+        set_variable("scene name", &scene.to_string());
+        set_variable("loading", &format!("{:?}", !memory.done_loading.current()?));
+        set_variable(
+            "difficulty",
+            &format!("{:?}", memory.level_difficulty.current()?),
+        );
+        set_variable("scoring time", &format!("{}", memory.lsd_time.current()?));
+        set_variable("parries", &format!("{}", memory.lsd_parries.current()?));
+        set_variable(
+            "super meter",
+            &format!("{}", memory.lsd_super_meter.current()?),
+        );
+        set_variable("coins", &format!("{}", memory.lsd_coins.current()?));
+        set_variable("hits", &format!("{}", memory.lsd_hits.current()?));
+        set_variable(
+            "use coins instead of super meter",
+            &format!("{}", memory.lsd_use_coins_instead.current()?),
+        );
+        set_variable("star skip counter raw", counter_raw);
+        set_variable("is run in progress", &format!("{}", is_run_in_progress));
+    }
 
     // For debugging
-    #[cfg(debug_assertions)]
-    {
+    if publishing(Group::Debug) {
         set_variable("previous scene", &previous_scene.to_string());
         set_variable(
             "last seen scene",
@@ -315,7 +275,9 @@ async fn tick<'a>(
                 && memory.level_time.current()? > 0f32
                 && (!memory.level_is_dice.current()? || memory.lsd_time.current()? == 0f32))
         {
-            set_variable("is run in progress", &format!("{}", true));
+            variables::set(Group::RunRecap, "is run in progress", || {
+                format!("{}", true)
+            });
             pause_game_time();
             start();
         }
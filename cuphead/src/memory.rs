@@ -2,37 +2,16 @@ use crate::enums::Grade;
 use crate::enums::Levels;
 use crate::enums::Mode;
 use asr::game_engine::unity::scene_manager::SceneManager;
-use asr::string::ArrayWString;
-use asr::{Address64, PointerSize};
-use helpers::watchers::unity::{GameObjectActivePath, MonoBehaviourFieldPath, UnityImage};
+use asr::Address64;
+use helpers::watchers::unity::{GameObjectActivePath, MonoBehaviourFieldPathBuilder, UnityImage};
 use helpers::watchers::Watcher;
 use std::error::Error;
 use std::rc::Rc;
 
-pub struct Offsets {
-    pub string_contents: &'static str,
-}
-
-impl Offsets {
-    pub fn new(size: PointerSize) -> Offsets {
-        match size {
-            PointerSize::Bit64 => Offsets {
-                string_contents: "0x14",
-            },
-            PointerSize::Bit32 => Offsets {
-                string_contents: "0xC",
-            },
-            _ => Offsets {
-                string_contents: "0x0", // n/a
-            },
-        }
-    }
-}
-
 pub struct Memory<'a> {
     pub done_loading: Watcher<'a, bool>,
     pub insta: Watcher<'a, Address64>,
-    pub scene: Watcher<'a, ArrayWString<128>>,
+    pub scene: Watcher<'a, String>,
     pub in_game: Watcher<'a, bool>,
     pub level: Watcher<'a, Levels>,
     pub level_won: Watcher<'a, bool>,
@@ -59,7 +38,6 @@ impl<'a> Memory<'a> {
         unity: UnityImage<'a>,
         scene_manager: Rc<SceneManager>,
     ) -> Result<Memory<'a>, Box<dyn Error>> {
-        let offsets = Offsets::new(unity.module.get_pointer_size());
         Ok(Memory {
             done_loading: Watcher::from(unity.path(
                 "SceneLoader",
@@ -68,11 +46,11 @@ impl<'a> Memory<'a> {
             ))
             .default_given(true),
             insta: Watcher::from(unity.path("SceneLoader", 0, &["_instance", "camera"])).default(),
-            scene: Watcher::from(unity.path(
-                "SceneLoader",
-                0,
-                &["<SceneName>k__BackingField", offsets.string_contents],
-            ))
+            scene: Watcher::from(
+                unity
+                    .path("SceneLoader", 0, &["<SceneName>k__BackingField"])
+                    .string(),
+            )
             .default(),
 
             in_game: Watcher::from(unity.path("PlayerData", 0, &["inGame"])).default_given(false),
@@ -165,27 +143,23 @@ impl<'a> Memory<'a> {
                 &["devil_cinematic_bad_ending_transition_0001"],
             ))
             .default(),
-            difficulty_ticker_started_counting: Watcher::from(MonoBehaviourFieldPath::init(
-                unity.process,
-                unity.module.clone(),
-                scene_manager.clone(),
-                "scene_win",
-                "WinScreen",
-                &["UI", "Canvas", "Scoring", "DifficultyTicker"],
-                "WinScreenTicker",
-                &["startedCounting"],
-            )?)
+            difficulty_ticker_started_counting: Watcher::from(
+                MonoBehaviourFieldPathBuilder::new(&unity, scene_manager.clone())
+                    .scene("scene_win")
+                    .game_object("WinScreen", ["UI", "Canvas", "Scoring", "DifficultyTicker"])
+                    .component("WinScreenTicker")
+                    .field("startedCounting")
+                    .build()?,
+            )
             .default(),
-            difficulty_ticker_finished_counting: Watcher::from(MonoBehaviourFieldPath::init(
-                unity.process,
-                unity.module.clone(),
-                scene_manager.clone(),
-                "scene_win",
-                "WinScreen",
-                &["UI", "Canvas", "Scoring", "DifficultyTicker"],
-                "WinScreenTicker",
-                &["<FinishedCounting>k__BackingField"],
-            )?)
+            difficulty_ticker_finished_counting: Watcher::from(
+                MonoBehaviourFieldPathBuilder::new(&unity, scene_manager.clone())
+                    .scene("scene_win")
+                    .game_object("WinScreen", ["UI", "Canvas", "Scoring", "DifficultyTicker"])
+                    .component("WinScreenTicker")
+                    .field("<FinishedCounting>k__BackingField")
+                    .build()?,
+            )
             .default(),
         })
     }
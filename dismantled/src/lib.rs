@@ -2,8 +2,11 @@
 extern crate helpers;
 use asr::settings::gui::Title;
 use asr::settings::Gui;
-use asr::{future::next_tick, timer, watcher::Watcher, Error, Process};
+use asr::{timer, watcher::Watcher, Error, Process};
+use helpers::error::SimpleError;
+use helpers::runtime::AutoSplitter;
 use std::collections::HashSet;
+use std::error::Error as StdError;
 use zdoom::pclass::PClass;
 use zdoom::{player::DVector3, GameAction, ZDoom, ZDoomVersion};
 
@@ -108,60 +111,73 @@ struct Settings {
 }
 
 async fn main() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        asr::print_message(&panic_info.to_string());
-    }));
-
-    asr::print_message("Hello, World!");
-
-    let mut settings = Settings::register();
+    helpers::runtime::run::<Dismantled>().await;
+}
 
-    loop {
-        let process = Process::wait_attach("lzdoom.exe").await;
-        process
-            .until_closes(async {
-                on_attach(&process, &mut settings).await.expect("problem");
-            })
-            .await;
-    }
+struct Dismantled<'a> {
+    process: &'a Process,
+    zdoom: ZDoom<'a>,
+    watchers: Watchers,
+    completed_splits: HashSet<String>,
 }
 
-async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Option<Error>> {
-    let (mut zdoom, _) =
-        ZDoom::wait_try_load(process, ZDoomVersion::Lzdoom3_82, "lzdoom.exe", |_| Ok(())).await;
+impl AutoSplitter for Dismantled<'_> {
+    const PROCESS_NAMES: &'static [&'static str] = &[
+        // Windows
+        "lzdoom.exe",
+        // Linux
+        "lzdoom",
+    ];
 
-    // zdoom.dump();
+    type Settings = Settings;
+    type State<'a> = Dismantled<'a>;
 
-    let mut watchers = Watchers::default();
-    let mut completed_splits = HashSet::new();
+    async fn load<'a>(
+        process: &'a Process,
+        process_name: &'static str,
+    ) -> Result<Dismantled<'a>, Box<dyn StdError>> {
+        let (zdoom, _) = ZDoom::wait_try_load(
+            process,
+            Some(ZDoomVersion::Lzdoom3_82),
+            process_name,
+            |_| Ok(()),
+        )
+        .await;
 
-    loop {
-        if !process.is_open() {
-            asr::print_message("process not open");
-            return Ok(());
-        }
+        // zdoom.dump();
 
-        settings.update();
+        Ok(Dismantled {
+            process,
+            zdoom,
+            watchers: Watchers::default(),
+            completed_splits: HashSet::new(),
+        })
+    }
 
-        let res = watchers.update(process, &mut zdoom);
-        if res.is_err() {
-            asr::print_message("failed updating watchers");
-            continue;
-        }
+    async fn tick(
+        dismantled: &mut Self::State<'_>,
+        _settings: &Settings,
+    ) -> Result<(), Box<dyn StdError>> {
+        let Dismantled {
+            process,
+            zdoom,
+            watchers,
+            completed_splits,
+        } = dismantled;
 
-        let states = watchers.to_states();
-        if states.is_none() {
-            asr::print_message("some watcher is empty");
-            continue;
-        }
+        watchers
+            .update(process, zdoom)
+            .map_err(|_| SimpleError::from("failed updating watchers"))?;
 
-        let (old, current) = states.unwrap();
+        let (old, current) = watchers
+            .to_states()
+            .ok_or(SimpleError::from("some watcher is empty"))?;
 
         if timer::state() == timer::TimerState::NotRunning
             && old.level == "map45"
             && current.level == "MAP01"
         {
-            completed_splits = HashSet::new();
+            *completed_splits = HashSet::new();
             timer::start();
         }
 
@@ -173,7 +189,7 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
 
             if old.level != current.level {
                 let key = &format!("_level_{}", current.level.to_lowercase());
-                split(key, &mut completed_splits);
+                split(key, completed_splits);
             }
 
             if !old.inventories.is_empty() {
@@ -181,7 +197,7 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
                     if !old.inventories.contains(&inventory) {
                         asr::print_message(&format!("Picked up {inventory}"));
                         let key = &format!("_item_{}", inventory.to_owned().to_lowercase());
-                        split(key, &mut completed_splits);
+                        split(key, completed_splits);
                     }
                 }
             }
@@ -190,11 +206,15 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
                 && current.player_pos == TRUE_ENDING_POSITION
                 && old.player_pos != current.player_pos
             {
-                split(&String::from("split_run_end"), &mut completed_splits);
+                split(&String::from("split_run_end"), completed_splits);
             }
         }
 
-        next_tick().await;
+        Ok(())
+    }
+
+    fn invalidate(dismantled: &mut Self::State<'_>) {
+        let _ = dismantled.zdoom.invalidate_cache();
     }
 }
 
@@ -207,8 +227,6 @@ impl_auto_splitter_state!(Watchers {
 
 impl Watchers {
     fn update(&mut self, process: &Process, zdoom: &mut ZDoom) -> Result<(), Option<Error>> {
-        zdoom.invalidate_cache().expect("");
-
         let gameaction = zdoom.gameaction().unwrap_or_default();
         self.gameaction.update(Some(gameaction));
 
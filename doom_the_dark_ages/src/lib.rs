@@ -1,9 +1,10 @@
 extern crate helpers;
 use crate::player::IdPlayer;
 use asr::signature::Signature;
-use asr::{future::next_tick, PointerSize, Process};
+use asr::{PointerSize, Process};
 use bytemuck::CheckedBitPattern;
 use helpers::memory::scan_rel;
+use helpers::runtime::{AutoSplitter, NoSettings};
 use helpers::watchers::pointer_path::PointerPath;
 use helpers::watchers::Watcher;
 use idtech::{IdTech, IdTechVersion};
@@ -15,20 +16,7 @@ mod player;
 asr::async_main!(stable);
 
 async fn main() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        asr::print_message(&panic_info.to_string());
-    }));
-
-    asr::print_message("Hello, World!");
-
-    loop {
-        let process = Process::wait_attach("DOOMTheDarkAges.exe").await;
-        process
-            .until_closes(async {
-                on_attach(&process).await.expect("problem");
-            })
-            .await;
-    }
+    helpers::runtime::run::<DoomTheDarkAges>().await;
 }
 
 static GAME_SYSTEM_LOCAL_SIG: Signature<17> =
@@ -43,15 +31,27 @@ enum IdGameSystemLocalState {
     InGame = 2,
 }
 
-async fn on_attach(process: &Process) -> Result<(), Box<dyn Error>> {
-    let idtech = helpers::try_load::wait_try_load::<IdTech, _, _>(async || {
-        IdTech::try_load(process, IdTechVersion::IdTech8, "DOOMTheDarkAges.exe").await
-    })
-    .await;
+struct DoomTheDarkAges;
+
+impl AutoSplitter for DoomTheDarkAges {
+    const PROCESS_NAMES: &'static [&'static str] = &["DOOMTheDarkAges.exe"];
+
+    type Settings = NoSettings;
+    type State<'a> = Memory<'a>;
 
-    let mut memory = Memory::init(process, idtech)?;
+    async fn load<'a>(
+        process: &'a Process,
+        process_name: &'static str,
+    ) -> Result<Memory<'a>, Box<dyn Error>> {
+        let idtech = IdTech::try_load(process, IdTechVersion::IdTech8, process_name).await?;
+
+        Memory::init(process, idtech)
+    }
 
-    while process.is_open() {
+    async fn tick(
+        memory: &mut Self::State<'_>,
+        _settings: &NoSettings,
+    ) -> Result<(), Box<dyn Error>> {
         let state = &memory.state;
         if state.changed()? {
             asr::print_message(&format!(
@@ -64,13 +64,12 @@ async fn on_attach(process: &Process) -> Result<(), Box<dyn Error>> {
         // let player = &memory.player;
         // let vel_x = player.velocity.x.current().unwrap_or(0f32);
 
-        // Prepare for the next iteration
-        memory.invalidate();
-
-        next_tick().await;
+        Ok(())
     }
 
-    Ok(())
+    fn invalidate(memory: &mut Self::State<'_>) {
+        memory.invalidate();
+    }
 }
 
 struct Memory<'a> {
@@ -92,8 +91,7 @@ impl<'a> Memory<'a> {
             game_system_local
         ));
 
-        // Get the classes we need - we assume that they exist by now,
-        //   if they don't, it's a fatal error and we shouldn't retry
+        // Get the classes we need - if they don't exist yet, the whole load is retried
 
         Ok(Memory {
             state: PointerPath::new(
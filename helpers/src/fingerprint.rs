@@ -0,0 +1,237 @@
+use crate::error::SimpleError;
+use crate::{info, warn};
+use asr::{timer, Address, Process};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The timer variable the result of `identify` is written to.
+pub const BUILD_VARIABLE: &str = "game build";
+
+const DOS_MAGIC: u16 = 0x5A4D; // MZ
+const DOS_E_LFANEW: u64 = 0x3C;
+const PE_MAGIC: u32 = 0x4550; // PE\0\0
+const PE_TIME_DATE_STAMP: u64 = 0x8;
+
+/// Largest code region we'll hash, so a bad range can't make us read the whole module.
+const MAX_HASHED_BYTES: u64 = 0x10000;
+
+/// Identifies a specific build of a module, so a splitter can tell game versions apart without
+/// relying on the user to pick the right one.
+///
+/// The module size is always available. The PE timestamp is only there for Windows binaries, and
+/// the code hash only if it was asked for with `with_code_hash` (useful when two builds happen to
+/// share a size and timestamp, which is rare but happens with repacks).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    pub module_size: u64,
+    pub timestamp: Option<u32>,
+    pub code_hash: Option<u64>,
+}
+
+impl Fingerprint {
+    pub fn read(process: &Process, module: &str) -> Result<Self, Box<dyn Error>> {
+        let (module_address, module_size) = process
+            .get_module_range(module)
+            .map_err(|_| SimpleError::from(&format!("failed to get range of module {module}")))?;
+
+        Ok(Fingerprint {
+            module_size,
+            timestamp: read_pe_timestamp(process, module_address),
+            code_hash: None,
+        })
+    }
+
+    /// Also hash `len` bytes at `offset` into the module, e.g. the start of the code section.
+    pub fn with_code_hash(
+        self,
+        process: &Process,
+        module: &str,
+        offset: u64,
+        len: u64,
+    ) -> Result<Self, Box<dyn Error>> {
+        let module_address = process
+            .get_module_address(module)
+            .map_err(|_| SimpleError::from(&format!("failed to get address of module {module}")))?;
+
+        if !can_hash(self.module_size, offset, len) {
+            return Err(SimpleError::from(&format!(
+                "code region 0x{offset:X} (+0x{len:X}) is out of range for hashing"
+            ))
+            .into());
+        }
+
+        let mut buf = vec![0u8; len as usize];
+        process
+            .read_into_buf(module_address + offset, &mut buf)
+            .map_err(|_| SimpleError::from("failed to read code region for hashing"))?;
+
+        Ok(Fingerprint {
+            code_hash: Some(fnv1a(&buf)),
+            ..self
+        })
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X}", self.module_size)?;
+
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "-{timestamp:08X}")?;
+        }
+
+        if let Some(code_hash) = self.code_hash {
+            write!(f, "-{code_hash:016X}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A build we know about, and whatever the splitter wants to associate with it (usually a version
+/// enum or a table of offsets).
+///
+/// `timestamp` and `code_hash` are only compared when they're given, so a build can be matched on
+/// size alone if that's all we know.
+pub struct KnownBuild<T> {
+    pub name: &'static str,
+    pub module_size: u64,
+    pub timestamp: Option<u32>,
+    pub code_hash: Option<u64>,
+    pub value: T,
+}
+
+impl<T> KnownBuild<T> {
+    pub fn matches(&self, fingerprint: &Fingerprint) -> bool {
+        self.module_size == fingerprint.module_size
+            && self
+                .timestamp
+                .is_none_or(|t| fingerprint.timestamp == Some(t))
+            && self
+                .code_hash
+                .is_none_or(|h| fingerprint.code_hash == Some(h))
+    }
+}
+
+/// Find the build matching the fingerprint, and show the result to the user as a timer variable.
+///
+/// When nothing matches, the variable says so along with the fingerprint, so it can be reported
+/// and added to the table. It's up to the caller what to do then - usually falling back to
+/// signature scanning.
+pub fn identify<'t, T>(
+    fingerprint: &Fingerprint,
+    builds: &'t [KnownBuild<T>],
+) -> Option<&'t KnownBuild<T>> {
+    let build = builds.iter().find(|build| build.matches(fingerprint));
+
+    match build {
+        Some(build) => {
+            info!("=> identified build {}", build.name);
+            timer::set_variable(BUILD_VARIABLE, build.name);
+        }
+        None => {
+            warn!("=> unknown build with fingerprint {fingerprint}");
+            timer::set_variable(
+                BUILD_VARIABLE,
+                &format!("unknown ({fingerprint}), please report this!"),
+            );
+        }
+    }
+
+    build
+}
+
+fn read_pe_timestamp(process: &Process, module_address: Address) -> Option<u32> {
+    if process.read::<u16>(module_address).ok()? != DOS_MAGIC {
+        return None;
+    }
+
+    let pe_header = module_address + process.read::<u32>(module_address + DOS_E_LFANEW).ok()?;
+    if process.read::<u32>(pe_header).ok()? != PE_MAGIC {
+        return None;
+    }
+
+    process.read::<u32>(pe_header + PE_TIME_DATE_STAMP).ok()
+}
+
+/// Whether `len` bytes at `offset` are inside the module, and few enough to hash. The offset
+/// comes from whatever the caller read, so it may be garbage.
+fn can_hash(module_size: u64, offset: u64, len: u64) -> bool {
+    len <= MAX_HASHED_BYTES
+        && offset
+            .checked_add(len)
+            .is_some_and(|end| end <= module_size)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(timestamp: Option<u32>, code_hash: Option<u64>) -> Fingerprint {
+        Fingerprint {
+            module_size: 0x1000,
+            timestamp,
+            code_hash,
+        }
+    }
+
+    fn build(timestamp: Option<u32>, code_hash: Option<u64>) -> KnownBuild<()> {
+        KnownBuild {
+            name: "test",
+            module_size: 0x1000,
+            timestamp,
+            code_hash,
+            value: (),
+        }
+    }
+
+    #[test]
+    fn can_hash_checks_bounds() {
+        assert!(can_hash(0x1000, 0x0, 0x1000));
+        assert!(can_hash(0x1000, 0xFFF, 0x1));
+        assert!(!can_hash(0x1000, 0x1000, 0x1));
+        assert!(!can_hash(0x100000, 0x0, MAX_HASHED_BYTES + 1));
+    }
+
+    #[test]
+    fn can_hash_doesnt_overflow() {
+        assert!(!can_hash(0x1000, u64::MAX, 0x10));
+        assert!(!can_hash(u64::MAX, u64::MAX - 0x8, 0x10));
+    }
+
+    #[test]
+    fn builds_match_on_what_they_give() {
+        let fingerprint = fingerprint(Some(0x1234), Some(0x5678));
+
+        assert!(build(None, None).matches(&fingerprint));
+        assert!(build(Some(0x1234), None).matches(&fingerprint));
+        assert!(build(Some(0x1234), Some(0x5678)).matches(&fingerprint));
+        assert!(!build(Some(0x4321), None).matches(&fingerprint));
+        assert!(!build(None, Some(0x8765)).matches(&fingerprint));
+    }
+
+    #[test]
+    fn builds_dont_match_missing_parts() {
+        assert!(!build(Some(0x1234), None).matches(&fingerprint(None, None)));
+        assert!(!KnownBuild {
+            module_size: 0x2000,
+            ..build(None, None)
+        }
+        .matches(&fingerprint(None, None)));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(fingerprint(None, None).to_string(), "1000");
+        assert_eq!(
+            fingerprint(Some(0xAB), Some(0xCD)).to_string(),
+            "1000-000000AB-00000000000000CD"
+        );
+    }
+}
@@ -1,13 +1,17 @@
 extern crate core;
 extern crate proc_macro;
 pub mod error;
+pub mod fingerprint;
+pub mod log;
 pub mod memory;
+pub mod runtime;
 pub mod settings;
 pub mod try_load;
+pub mod variables;
 pub mod watchers;
 
 use crate::error::SimpleError;
-use asr::{print_message, timer};
+use asr::timer;
 pub use paste::paste;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
@@ -35,8 +39,7 @@ macro_rules! impl_auto_splitter_state {
                         $($field: $field.current.to_owned(),)+
                     };
 
-                    #[cfg(debug_assertions)]
-                    {
+                    if $crate::variables::publishing($crate::variables::Group::Memory) {
                         $(timer::set_variable(stringify!($field), &format!("{:#?}", current.$field));)+
                     }
 
@@ -79,62 +82,62 @@ pub fn better_split(
     setting_defaults: &HashMap<String, bool>,
     completed_splits: &mut HashSet<String>,
 ) -> Result<bool, Box<dyn Error>> {
-    print_message(&format!("trying to split {key}"));
+    trace!("trying to split {key}");
     if completed_splits.contains(key) {
-        print_message(&format!("-> {key} already split"));
+        debug!("-> {key} already split");
         return Ok(false);
     }
 
     let value = get_setting(key, setting_defaults)?;
     if !value {
-        print_message(&format!("-> {key} set to false"));
+        debug!("-> {key} set to false");
         return Ok(false);
     }
 
     if !completed_splits.insert(key.to_owned()) {
-        print_message(&format!("-> {key} already split"));
+        debug!("-> {key} already split");
         return Ok(false);
     }
 
-    print_message(&format!("-> {key} split!"));
+    info!("-> {key} split!");
     timer::split();
 
     Ok(true)
 }
 
 pub fn split(key: &String, completed_splits: &mut HashSet<String>) -> bool {
-    print_message(&format!("trying to split {key}"));
+    trace!("trying to split {key}");
     let settings_map = asr::settings::Map::load();
 
     if completed_splits.contains(key) {
-        print_message(&format!("-> {key} already split"));
+        debug!("-> {key} already split");
         return false;
     }
 
     let value = settings_map.get(key);
 
     if value.is_none() {
-        print_message(&format!("-> {key} not found"));
+        debug!("-> {key} not found");
         return false;
     }
 
     let value = value.unwrap().get_bool();
     if value.is_none() {
-        print_message(&format!("-> {key} not a bool"));
+        warn!("-> {key} not a bool");
         return false;
     }
 
     if !value.unwrap() {
-        print_message(&format!("-> {key} set to false"));
+        debug!("-> {key} set to false");
         return false;
     }
 
     if !completed_splits.insert(key.to_owned()) {
-        print_message(&format!("-> {key} already split"));
+        debug!("-> {key} already split");
         return false;
     }
 
-    print_message(&format!("-> {key} split!"));
+    info!("-> {key} split!");
     timer::split();
 
     true
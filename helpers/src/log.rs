@@ -0,0 +1,137 @@
+use asr::settings::Gui;
+use std::cell::RefCell;
+use std::fmt::{Arguments, Display, Formatter};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much to log. Anything below the chosen level is skipped, so the default only shows what's
+/// useful in a normal run.
+#[derive(Gui, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Trace (everything, very noisy)
+    Trace,
+    /// Debug
+    Debug,
+    /// Info
+    #[default]
+    Info,
+    /// Warnings
+    Warn,
+    /// Errors only
+    Error,
+}
+
+impl Level {
+    fn from_u8(value: u8) -> Level {
+        match value {
+            0 => Level::Trace,
+            1 => Level::Debug,
+            2 => Level::Info,
+            3 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The verbosity setting, registered by `helpers::runtime::run` after the splitter's own settings.
+#[derive(Gui)]
+pub struct LogSettings {
+    /// Log verbosity
+    ///
+    /// How much the splitter writes to the log. Turn this up when reporting a problem.
+    log_verbosity: Level,
+}
+
+impl LogSettings {
+    pub fn level(&self) -> Level {
+        self.log_verbosity
+    }
+}
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+thread_local! {
+    static TARGET_LEVELS: RefCell<Vec<(&'static str, Level)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn set_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::from_u8(MAX_LEVEL.load(Ordering::Relaxed))
+}
+
+/// Override the level for every target starting with `prefix` (e.g. `"zdoom"` or
+/// `"zdoom::level"`), regardless of the verbosity setting. The longest matching prefix wins.
+pub fn set_target_level(prefix: &'static str, level: Level) {
+    TARGET_LEVELS.with_borrow_mut(|levels| {
+        levels.retain(|(p, _)| *p != prefix);
+        levels.push((prefix, level));
+    });
+}
+
+pub fn enabled(level: Level, target: &str) -> bool {
+    let target_level = TARGET_LEVELS.with_borrow(|levels| {
+        levels
+            .iter()
+            .filter(|(prefix, _)| target.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+    });
+
+    level >= target_level.unwrap_or_else(self::level)
+}
+
+/// Use the macros instead, which fill in the target and skip formatting if the level is disabled.
+pub fn log(level: Level, target: &str, args: Arguments) {
+    asr::print_message(&format!("[{level} {target}] {args}"));
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::log::enabled(level, module_path!()) {
+            $crate::log::log(level, module_path!(), format_args!($($arg)+));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
@@ -1,4 +1,7 @@
 use crate::error::SimpleError;
+use crate::fingerprint::Fingerprint;
+use crate::{debug, warn};
+use asr::settings::Value;
 use asr::signature::Signature;
 use asr::{Address, Process};
 use std::error::Error;
@@ -41,3 +44,138 @@ pub fn scan_rel(
             .map_err(|_| SimpleError::from(&format!("unable to read from address 0x{}", addr)))?
         + next_instruction)
 }
+
+/// Find the first occurrence of `needle` in the range, reading it a chunk at a time. Chunks that
+/// can't be read (e.g. unmapped pages) are skipped.
+pub fn find_bytes(
+    process: &Process,
+    (start, len): (impl Into<Address>, u64),
+    needle: &[u8],
+) -> Option<Address> {
+    const CHUNK_SIZE: u64 = 0x10000;
+
+    let start = start.into();
+    if needle.is_empty() || (needle.len() as u64) > CHUNK_SIZE {
+        return None;
+    }
+
+    let mut buf = vec![0; CHUNK_SIZE as usize];
+    // chunks overlap by all but one byte of the needle, so we don't miss one that straddles two
+    let step = CHUNK_SIZE - needle.len() as u64 + 1;
+    let mut offset = 0;
+    while offset < len {
+        let size = CHUNK_SIZE.min(len - offset) as usize;
+        let chunk = &mut buf[..size];
+        if process.read_into_buf(start + offset, chunk).is_ok() {
+            if let Some(i) = chunk.windows(needle.len()).position(|w| w == needle) {
+                return Some(start + offset + i as u64);
+            }
+        }
+
+        offset += step;
+    }
+
+    None
+}
+
+/// Read a string up to a NUL (or `max_len` bytes).
+pub fn read_c_string(
+    process: &Process,
+    addr: Address,
+    max_len: usize,
+) -> Result<String, Box<dyn Error>> {
+    let mut buf = vec![0; max_len];
+    process
+        .read_into_buf(addr, &mut buf)
+        .map_err(|_| SimpleError::from(&format!("unable to read string at 0x{}", addr)))?;
+
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(max_len);
+    Ok(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+/// Remembers where scans landed, as offsets into a module, in the settings map so they survive
+/// restarts of the splitter and the game.
+///
+/// Each entry is tagged with the module's fingerprint, so a game update simply misses the cache
+/// instead of handing back stale offsets, and the entry is replaced by the next scan (so the
+/// settings don't grow with every update). Every cached offset still goes through a (cheap) sanity
+/// check before being trusted.
+pub struct ScanCache {
+    module_address: Address,
+    module_size: u64,
+    prefix: String,
+    fingerprint: String,
+}
+
+impl ScanCache {
+    pub fn new(process: &Process, module: &str) -> Result<Self, Box<dyn Error>> {
+        let fingerprint = Fingerprint::read(process, module)?;
+        let (module_address, module_size) = process
+            .get_module_range(module)
+            .map_err(|_| SimpleError::from(&format!("failed to get range of module {}", module)))?;
+
+        Ok(ScanCache {
+            module_address,
+            module_size,
+            prefix: format!("_scan_cache_{module}"),
+            fingerprint: fingerprint.to_string(),
+        })
+    }
+
+    /// Get the address for `name` from the cache if it's there and `is_valid` agrees with it,
+    /// otherwise run `scan` and remember what it found.
+    ///
+    /// Only addresses inside the module are cached, anything else (e.g. a pointer that was read
+    /// from the module) can't be expected to be in the same place next time.
+    pub fn resolve<E>(
+        &self,
+        name: &str,
+        is_valid: impl Fn(Address) -> bool,
+        scan: impl FnOnce() -> Result<Address, E>,
+    ) -> Result<Address, E> {
+        let key = format!("{}_{name}", self.prefix);
+
+        // stored as "{fingerprint}:{offset:X}"
+        let cached = asr::settings::Map::load()
+            .get(&key)
+            .and_then(|value| value.get_string());
+        let offset = cached.as_deref().and_then(|cached| {
+            let (fingerprint, offset) = cached.split_once(':')?;
+            (fingerprint == self.fingerprint)
+                .then(|| u64::from_str_radix(offset, 16).ok())
+                .flatten()
+        });
+
+        if let Some(offset) = offset {
+            let addr = self.module_address + offset;
+            if is_valid(addr) {
+                debug!("=> found {name} at 0x{addr} in the scan cache");
+                return Ok(addr);
+            }
+
+            warn!("=> cached address 0x{addr} for {name} looks wrong, scanning again");
+        }
+
+        let addr = scan()?;
+
+        let offset = addr.value().wrapping_sub(self.module_address.value());
+        if offset < self.module_size {
+            let value = format!("{}:{offset:X}", self.fingerprint);
+            store_setting(&key, &Value::from(value.as_str()));
+        }
+
+        Ok(addr)
+    }
+}
+
+fn store_setting(key: &str, value: &Value) {
+    loop {
+        let old = asr::settings::Map::load();
+        let new = old.clone();
+        new.insert(key, value);
+
+        if new.store_if_unchanged(&old) {
+            break;
+        }
+    }
+}
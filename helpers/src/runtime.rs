@@ -0,0 +1,130 @@
+use crate::log::{self, LogSettings};
+use crate::try_load::wait_try_load_millis;
+use crate::variables::{self, VariableSettings};
+use crate::{info, warn};
+use asr::future::{next_tick, retry};
+use asr::settings::Gui;
+use asr::{print_message, Process};
+use std::error::Error;
+use std::future::Future;
+use std::time::Duration;
+
+/// The lifecycle of a splitter, so each game crate only has to say how to load its memory and
+/// what to do every tick. `run` owns the rest - attaching, retrying loads, settings, and reporting
+/// errors.
+pub trait AutoSplitter {
+    /// Process names to attach to, in order of priority. If more than one is running, the one
+    /// earliest in the list wins.
+    const PROCESS_NAMES: &'static [&'static str];
+
+    /// How long to wait between unsuccessful loads.
+    const LOAD_COOLDOWN: Duration = Duration::from_millis(100);
+
+    type Settings: Gui;
+
+    /// Whatever the splitter needs to keep around while attached. It's dropped when the process
+    /// closes, so it may borrow the process.
+    type State<'a>;
+
+    /// Called after attaching, and again every `LOAD_COOLDOWN` until it succeeds.
+    /// `process_name` is the entry of `PROCESS_NAMES` that was attached to.
+    fn load<'a>(
+        process: &'a Process,
+        process_name: &'static str,
+    ) -> impl Future<Output = Result<Self::State<'a>, Box<dyn Error>>>;
+
+    /// Called once per tick while the process is open. Errors are reported, but don't stop the
+    /// splitter - the next tick gets a fresh try.
+    fn tick(
+        state: &mut Self::State<'_>,
+        settings: &Self::Settings,
+    ) -> impl Future<Output = Result<(), Box<dyn Error>>>;
+
+    /// Called before every tick, to invalidate any cached watchers.
+    fn invalidate(_state: &mut Self::State<'_>) {}
+
+    /// Called once the process has closed, if it got as far as loading.
+    fn on_detach(_state: Self::State<'_>) {}
+}
+
+/// For splitters that don't have any settings (yet).
+#[derive(Gui)]
+pub struct NoSettings {}
+
+/// Run the splitter forever. This is all `main` needs to do:
+///
+/// ```ignore
+/// asr::async_main!(stable);
+///
+/// async fn main() {
+///     helpers::runtime::run::<MyGame>().await;
+/// }
+/// ```
+pub async fn run<S: AutoSplitter>() {
+    std::panic::set_hook(Box::new(|panic_info| {
+        print_message(&panic_info.to_string());
+    }));
+
+    let mut settings = S::Settings::register();
+    let mut log_settings = LogSettings::register();
+    log::set_level(log_settings.level());
+    let mut variable_settings = VariableSettings::register();
+
+    info!("Hello, World!");
+
+    loop {
+        let (process, process_name) = retry(|| attach(S::PROCESS_NAMES)).await;
+        info!("=> attached to {process_name}");
+
+        let mut state = None;
+        process
+            .until_closes(async {
+                let state = state.insert(
+                    wait_try_load_millis(|| S::load(&process, process_name), S::LOAD_COOLDOWN)
+                        .await,
+                );
+
+                let mut last_error = None;
+                while process.is_open() {
+                    settings.update();
+                    log_settings.update();
+                    log::set_level(log_settings.level());
+                    variable_settings.update();
+                    variables::update(&variable_settings);
+                    S::invalidate(state);
+
+                    let error = S::tick(state, &settings).await.err().map(|e| e.to_string());
+                    report_tick_error(&last_error, &error);
+                    last_error = error;
+
+                    next_tick().await;
+                }
+            })
+            .await;
+
+        if let Some(state) = state {
+            S::on_detach(state);
+        }
+
+        info!("=> detached from process");
+        next_tick().await;
+    }
+}
+
+fn attach(process_names: &[&'static str]) -> Option<(Process, &'static str)> {
+    process_names
+        .iter()
+        .find_map(|name| Some((Process::attach(name)?, *name)))
+}
+
+/// Tick errors tend to repeat every tick (e.g. while in a menu), so only report when it changes.
+fn report_tick_error(last_error: &Option<String>, error: &Option<String>) {
+    if last_error == error {
+        return;
+    }
+
+    match error {
+        Some(error) => warn!("=> tick failed: {error}"),
+        None => info!("=> tick recovered"),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{debug, info};
 use std::error::Error;
 use std::future::Future;
 use std::time::Duration;
@@ -15,7 +16,7 @@ where
     F: Fn() -> Fut,
     Fut: Future<Output = Result<T, Box<dyn Error>>>,
 {
-    asr::print_message("=> attempting try_load");
+    debug!("=> attempting try_load");
 
     let result = loop {
         let result = load_fn().await;
@@ -25,15 +26,15 @@ where
             Err(e) => e,
         };
 
-        asr::print_message(&format!(
+        debug!(
             "=> try_load unsuccessful, trying again in {}ms! with error: {}",
             cooldown.as_millis(),
             error
-        ));
+        );
         asr::future::sleep(cooldown).await;
     };
 
-    asr::print_message("=> try_load successful!");
+    info!("=> try_load successful!");
 
     result
 }
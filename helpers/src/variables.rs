@@ -0,0 +1,108 @@
+use asr::settings::Gui;
+use asr::timer;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// A group of timer variables that can be turned on and off together in the settings.
+///
+/// Variables runners rely on directly (e.g. a level timer) shouldn't be in a group - just set
+/// them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Group {
+    /// Read by the run recap component, so they should be up-to-date every tick.
+    RunRecap,
+    /// Splitter state, for diagnosing bug reports.
+    Debug,
+    /// Raw values read from memory.
+    Memory,
+}
+
+const GROUPS: usize = 3;
+
+impl Group {
+    fn index(self) -> usize {
+        match self {
+            Group::RunRecap => 0,
+            Group::Debug => 1,
+            Group::Memory => 2,
+        }
+    }
+
+    /// How often the group is published at most. Debug values tend to be expensive to format and
+    /// nobody can read them at 120 ticks per second anyway.
+    fn min_interval(self) -> Duration {
+        match self {
+            Group::RunRecap => Duration::ZERO,
+            Group::Debug => Duration::from_millis(100),
+            Group::Memory => Duration::from_millis(250),
+        }
+    }
+}
+
+/// The toggles for each group, registered by `helpers::runtime::run` after the splitter's own
+/// settings.
+#[derive(Gui)]
+pub struct VariableSettings {
+    /// Show run recap variables
+    ///
+    /// Used by the run recap component. Turn this off if you don't use it, to save a little
+    /// performance.
+    #[default = true]
+    variables_run_recap: bool,
+    /// Show debug variables
+    ///
+    /// The splitter's internal state. Useful to include when reporting a bug.
+    variables_debug: bool,
+    /// Show memory variables
+    ///
+    /// Raw values the splitter reads from the game.
+    variables_memory: bool,
+}
+
+impl VariableSettings {
+    fn enabled(&self, group: Group) -> bool {
+        match group {
+            Group::RunRecap => self.variables_run_recap,
+            Group::Debug => self.variables_debug,
+            Group::Memory => self.variables_memory,
+        }
+    }
+}
+
+thread_local! {
+    static PUBLISHING: Cell<[bool; GROUPS]> = const { Cell::new([false; GROUPS]) };
+    static LAST_PUBLISHED: Cell<[Option<Instant>; GROUPS]> = const { Cell::new([None; GROUPS]) };
+}
+
+/// Decide which groups get published this tick. Called once per tick by the runtime, so every
+/// variable in a group is published on the same tick.
+pub fn update(settings: &VariableSettings) {
+    let now = Instant::now();
+    let mut publishing = [false; GROUPS];
+    let mut last_published = LAST_PUBLISHED.get();
+
+    for group in [Group::RunRecap, Group::Debug, Group::Memory] {
+        let i = group.index();
+        publishing[i] = settings.enabled(group)
+            && last_published[i].is_none_or(|last| now - last >= group.min_interval());
+
+        if publishing[i] {
+            last_published[i] = Some(now);
+        }
+    }
+
+    PUBLISHING.set(publishing);
+    LAST_PUBLISHED.set(last_published);
+}
+
+/// Whether the group should be published this tick. Wrap anything expensive to compute in this.
+pub fn publishing(group: Group) -> bool {
+    PUBLISHING.get()[group.index()]
+}
+
+/// Set a variable in a group. The value is only formatted if the group is being published.
+pub fn set(group: Group, key: &str, value: impl FnOnce() -> String) {
+    if publishing(group) {
+        timer::set_variable(key, &value());
+    }
+}
@@ -0,0 +1,238 @@
+//! `PointerPathReadable` for the asr emulators, each behind the feature of the same name.
+//!
+//! Addresses are whatever the emulator's `read` takes, which is the console's own address space
+//! (e.g. `0x02000000` for GBA EWRAM, `0x80000000` for PS1 and GameCube main RAM). Pointers are read
+//! with the console's width, so the `pointer_size` given to a `PointerPath` is ignored here.
+//!
+//! Values always come back in native byte order. Big endian consoles (Genesis, GameCube, Wii)
+//! report it through `endian`, so pointers are decoded correctly, and leave values to asr's `read`,
+//! which converts them. That only works for numbers (and arrays of them), so `PointerPath::read`
+//! only takes those on big endian consoles.
+//!
+//! NES and SNES aren't here because asr has no emulator support for them to build on. Finding
+//! their RAM in each emulator is a bigger job than this module, and belongs in asr.
+
+/// `$feature` is both the feature and the asr module. `$pointer` and `$endian` are the console's
+/// pointer width and byte order, and `$mask` is the part of a pointer that's actually an address
+/// (e.g. the 68000 only has a 24 bit bus, so games often keep garbage in the top byte). `$bound` is
+/// what asr's `read` needs on top of `CheckedBitPattern`, i.e. `FromEndian` on big endian consoles,
+/// where it converts the value.
+///
+/// Paths are spelled out in full, since none of them are used without a feature on.
+macro_rules! impl_emulator_readable {
+    ($feature:literal, $console:ident, $pointer:ty, $endian:ident, $mask:expr $(, $bound:path)?) => {
+        #[cfg(feature = $feature)]
+        impl crate::watchers::pointer_path::PointerPathReadable
+            for asr::emulator::$console::Emulator
+        {
+            fn endian(&self) -> crate::watchers::pointer_path::Endian {
+                crate::watchers::pointer_path::Endian::$endian
+            }
+
+            fn read_pointer(
+                &self,
+                address: asr::Address,
+                _pointer_size: asr::PointerSize,
+            ) -> Result<asr::Address, Box<dyn std::error::Error>> {
+                let bytes = self
+                    .read::<[u8; size_of::<$pointer>()]>(address.value() as u32)
+                    .map_err(|_| {
+                        crate::error::SimpleError::from(&format!(
+                            "unable to read pointer at {address}"
+                        ))
+                    })?;
+
+                Ok(asr::Address::new(self.endian().decode_unsigned(&bytes) & $mask))
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<T: bytemuck::CheckedBitPattern $(+ $bound)?> crate::watchers::pointer_path::ReadValue<T>
+            for asr::emulator::$console::Emulator
+        {
+            fn read_value(&self, address: asr::Address) -> Result<T, Box<dyn std::error::Error>> {
+                self.read::<T>(address.value() as u32).map_err(|_| {
+                    crate::error::SimpleError::from(&format!("unable to read value at {address}"))
+                        .into()
+                })
+            }
+        }
+    };
+}
+
+impl_emulator_readable!("gb", gb, u16, Little, 0xFFFF);
+impl_emulator_readable!("sms", sms, u16, Little, 0xFFFF);
+impl_emulator_readable!("gba", gba, u32, Little, 0xFFFF_FFFF);
+impl_emulator_readable!("genesis", genesis, u32, Big, 0x00FF_FFFF, asr::FromEndian);
+impl_emulator_readable!("ps1", ps1, u32, Little, 0xFFFF_FFFF);
+impl_emulator_readable!("ps2", ps2, u32, Little, 0xFFFF_FFFF);
+impl_emulator_readable!("gcn", gcn, u32, Big, 0xFFFF_FFFF, asr::FromEndian);
+impl_emulator_readable!("wii", wii, u32, Big, 0xFFFF_FFFF, asr::FromEndian);
+
+/// A refresh rate as an exact fraction (`frames` per `seconds`), so converting frame counts to a
+/// duration doesn't drift like multiplying by a rounded float would.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefreshRate {
+    pub frames: u64,
+    pub seconds: u64,
+}
+
+impl RefreshRate {
+    /// 4.194304 MHz / 70224 cycles per frame, about 59.7275 Hz
+    pub const GB: RefreshRate = RefreshRate::new(4_194_304, 70_224);
+    /// 16.777216 MHz / 280896 cycles per frame, about 59.7275 Hz
+    pub const GBA: RefreshRate = RefreshRate::new(16_777_216, 280_896);
+    /// 53.693175 MHz / (3420 * 262), about 59.9227 Hz
+    pub const GENESIS_NTSC: RefreshRate = RefreshRate::new(53_693_175, 3_420 * 262);
+    /// 53.203424 MHz / (3420 * 313), about 49.7015 Hz
+    pub const GENESIS_PAL: RefreshRate = RefreshRate::new(53_203_424, 3_420 * 313);
+    /// The Master System shares its video timing with the Genesis.
+    pub const SMS_NTSC: RefreshRate = RefreshRate::GENESIS_NTSC;
+    pub const SMS_PAL: RefreshRate = RefreshRate::GENESIS_PAL;
+    /// (315 / 88 * 15) MHz / (3413 * 263), about 59.8173 Hz
+    pub const PS1_NTSC: RefreshRate = RefreshRate::new(4_725_000_000, 88 * 3_413 * 263);
+    /// 53.203425 MHz / (3406 * 314), about 49.7465 Hz
+    pub const PS1_PAL: RefreshRate = RefreshRate::new(53_203_425, 3_406 * 314);
+    /// Broadcast NTSC, 59.94 Hz, which the later consoles (PS2, GameCube, Wii) output
+    pub const NTSC: RefreshRate = RefreshRate::new(60_000, 1_001);
+    /// Broadcast PAL, 50 Hz
+    pub const PAL: RefreshRate = RefreshRate::new(50, 1);
+
+    pub const fn new(frames: u64, seconds: u64) -> Self {
+        RefreshRate { frames, seconds }
+    }
+
+    pub fn hz(&self) -> f64 {
+        self.frames as f64 / self.seconds as f64
+    }
+
+    /// How long `frames` frames take at this rate, rounded down to the nanosecond.
+    pub fn duration(&self, frames: u64) -> asr::time::Duration {
+        self.wide_duration(frames as u128)
+    }
+
+    /// `duration`, for frame counts past a u64 (a 64 bit counter that wrapped).
+    fn wide_duration(&self, frames: u128) -> asr::time::Duration {
+        let nanos = frames * self.seconds as u128 * 1_000_000_000 / self.frames as u128;
+
+        asr::time::Duration::new(
+            (nanos / 1_000_000_000) as i64,
+            (nanos % 1_000_000_000) as i32,
+        )
+    }
+}
+
+/// Turns a frame counter from the game into a duration, counting the times it wraps around.
+///
+/// A drop of more than half the counter's range is taken as a wrap (the counter overflowed), and
+/// anything smaller as the game resetting it. Call `reset` when the game resets it to something
+/// that isn't smaller, e.g. on a new run.
+#[derive(Clone, Debug)]
+pub struct FrameCounter {
+    rate: RefreshRate,
+    /// The width of the counter, at most 64
+    bits: u32,
+    last: Option<u64>,
+    wraps: u64,
+}
+
+impl FrameCounter {
+    /// `bits` is the width of the counter in memory, e.g. 32 for a u32.
+    pub fn new(rate: RefreshRate, bits: u32) -> Self {
+        FrameCounter {
+            rate,
+            bits: bits.min(64),
+            last: None,
+            wraps: 0,
+        }
+    }
+
+    /// The largest value the counter can hold. A 64 bit counter is handled separately, since
+    /// `1 << 64` doesn't fit.
+    fn max(&self) -> u64 {
+        if self.bits == 64 {
+            u64::MAX
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+
+    pub fn update(&mut self, raw: u64) -> asr::time::Duration {
+        let max = self.max();
+        let raw = raw & max;
+        match self.last {
+            Some(last) if raw < last && last.wrapping_sub(raw) > max / 2 => self.wraps += 1,
+            Some(last) if raw < last => self.wraps = 0,
+            _ => {}
+        }
+        self.last = Some(raw);
+
+        let frames = ((self.wraps as u128) << self.bits) + raw as u128;
+        self.rate.wide_duration(frames)
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.wraps = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asr::time::Duration;
+
+    #[test]
+    fn refresh_rates_are_exact_fractions() {
+        assert_eq!(RefreshRate::NTSC.duration(60_000), Duration::seconds(1_001));
+        assert_eq!(
+            RefreshRate::GBA.duration(16_777_216),
+            Duration::seconds(280_896)
+        );
+        assert_eq!(RefreshRate::PAL.duration(1), Duration::milliseconds(20));
+        assert!((RefreshRate::GBA.hz() - 59.7275).abs() < 0.0001);
+        assert!((RefreshRate::PS1_NTSC.hz() - 59.8173).abs() < 0.0001);
+    }
+
+    #[test]
+    fn refresh_rate_duration_rounds_down() {
+        // 1001 / 60000 seconds is 16683333.3 nanoseconds
+        assert_eq!(
+            RefreshRate::NTSC.duration(1),
+            Duration::nanoseconds(16_683_333)
+        );
+    }
+
+    #[test]
+    fn frame_counter_counts_wraps() {
+        let mut counter = FrameCounter::new(RefreshRate::new(1, 1), 16);
+
+        assert_eq!(counter.update(65_530), Duration::seconds(65_530));
+        assert_eq!(counter.update(5), Duration::seconds(65_541));
+        assert_eq!(counter.update(65_535), Duration::seconds(131_071));
+        assert_eq!(counter.update(0), Duration::seconds(131_072));
+    }
+
+    #[test]
+    fn frame_counter_treats_small_drops_as_resets() {
+        let mut counter = FrameCounter::new(RefreshRate::new(1, 1), 16);
+
+        counter.update(65_530);
+        counter.update(5);
+        assert_eq!(counter.update(2), Duration::seconds(2));
+
+        counter.reset();
+        assert_eq!(counter.update(1_000), Duration::seconds(1_000));
+    }
+
+    #[test]
+    fn frame_counter_handles_64_bits() {
+        let mut counter = FrameCounter::new(RefreshRate::new(1 << 32, 1), 64);
+
+        assert_eq!(
+            counter.update(u64::MAX),
+            Duration::new(u32::MAX as i64, 999_999_999)
+        );
+        assert_eq!(counter.update(1 << 32), Duration::seconds((1 << 32) + 1));
+    }
+}
@@ -1,22 +1,167 @@
 use crate::error::SimpleError;
-use crate::watchers::pointer_path::PointerPathReadable;
-#[cfg(feature = "gba")]
+use crate::watchers::emulators::RefreshRate;
 use asr::emulator::gba::Emulator;
-use asr::{Address, PointerSize};
-use bytemuck::CheckedBitPattern;
 use std::error::Error;
+use std::fmt::{Display, Formatter};
 
-#[cfg(feature = "gba")]
-impl PointerPathReadable for Emulator {
-    fn read_pointer_path<T: CheckedBitPattern>(
-        &self,
-        address: impl Into<Address>,
-        _pointer_size: PointerSize,
-        path: &[u64],
-    ) -> Result<T, Box<dyn Error>> {
-        let path = path.iter().map(|o| *o as u32).collect::<Vec<u32>>();
-        let path = path.as_slice();
-        self.read_pointer_path::<T>(address.into().value() as u32, path)
-            .map_err(|_| SimpleError::from("unable to read value from pointer path").into())
+/// The GBA's real refresh rate, for converting the frame counters games keep.
+pub const REFRESH_RATE: RefreshRate = RefreshRate::GBA;
+
+/// Where the cartridge header fields we care about live, in the ROM (which is mapped at
+/// 0x08000000).
+const TITLE_ADDRESS: u32 = 0x080000A0;
+const GAME_CODE_ADDRESS: u32 = 0x080000AC;
+const MAKER_CODE_ADDRESS: u32 = 0x080000B0;
+const VERSION_ADDRESS: u32 = 0x080000BC;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+    Japan,
+    Usa,
+    Europe,
+    Germany,
+    France,
+    Spain,
+    Italy,
+    Other(char),
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::Japan => write!(f, "JP"),
+            Region::Usa => write!(f, "US"),
+            Region::Europe => write!(f, "EU"),
+            Region::Germany => write!(f, "DE"),
+            Region::France => write!(f, "FR"),
+            Region::Spain => write!(f, "ES"),
+            Region::Italy => write!(f, "IT"),
+            Region::Other(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// The identifying bits of a GBA cartridge header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RomHeader {
+    /// Up to 12 characters, e.g. "KURUKURU"
+    pub title: String,
+    /// 4 characters, the last of which is the region, e.g. "AKRP"
+    pub game_code: String,
+    /// 2 characters, e.g. "01" for Nintendo
+    pub maker_code: String,
+    /// The revision of the ROM, usually 0
+    pub version: u8,
+}
+
+impl RomHeader {
+    pub fn read(emulator: &Emulator) -> Result<Self, Box<dyn Error>> {
+        let read_bytes = |address: u32, what: &str| {
+            emulator
+                .read::<[u8; 12]>(address)
+                .map_err(|_| SimpleError::from(&format!("couldnt read the rom's {what}")))
+        };
+
+        let title = read_bytes(TITLE_ADDRESS, "title")?;
+        let game_code = read_bytes(GAME_CODE_ADDRESS, "game code")?;
+        let maker_code = read_bytes(MAKER_CODE_ADDRESS, "maker code")?;
+        let version = emulator
+            .read::<u8>(VERSION_ADDRESS)
+            .map_err(|_| SimpleError::from("couldnt read the rom's version"))?;
+
+        Self::parse(&title, &game_code[..4], &maker_code[..2], version)
+    }
+
+    /// Build a header from the raw fields, which are null padded. Errors if the game code isn't
+    /// one, which is what an emulator without a game loaded gives us.
+    pub fn parse(
+        title: &[u8],
+        game_code: &[u8],
+        maker_code: &[u8],
+        version: u8,
+    ) -> Result<Self, Box<dyn Error>> {
+        let to_string = |bytes: &[u8]| -> String {
+            bytes
+                .iter()
+                .take_while(|b| **b != 0)
+                .map(|b| *b as char)
+                .collect()
+        };
+
+        let game_code = to_string(game_code);
+        if game_code.len() != 4 || !game_code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SimpleError::from(&format!(
+                "no valid rom header (game code {game_code:?}), is a game loaded?"
+            ))
+            .into());
+        }
+
+        Ok(RomHeader {
+            title: to_string(title),
+            game_code,
+            maker_code: to_string(maker_code),
+            version,
+        })
+    }
+
+    pub fn region(&self) -> Region {
+        match self.game_code.chars().nth(3).unwrap_or('?') {
+            'J' => Region::Japan,
+            'E' => Region::Usa,
+            'P' => Region::Europe,
+            'D' => Region::Germany,
+            'F' => Region::France,
+            'S' => Region::Spain,
+            'I' => Region::Italy,
+            c => Region::Other(c),
+        }
+    }
+}
+
+impl Display for RomHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}-{}, {}, rev {})",
+            self.title,
+            self.game_code,
+            self.maker_code,
+            self.region(),
+            self.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_padded_fields() {
+        let header = RomHeader::parse(b"KURUKURU\0\0\0\0", b"AKRP", b"01", 1).unwrap();
+
+        assert_eq!(header.title, "KURUKURU");
+        assert_eq!(header.game_code, "AKRP");
+        assert_eq!(header.maker_code, "01");
+        assert_eq!(header.version, 1);
+        assert_eq!(header.to_string(), "KURUKURU (AKRP-01, EU, rev 1)");
+    }
+
+    #[test]
+    fn parse_rejects_missing_game_code() {
+        assert!(RomHeader::parse(&[0; 12], &[0; 4], &[0; 2], 0).is_err());
+        assert!(RomHeader::parse(b"KURUKURU", b"AK\0\0", b"01", 0).is_err());
+        assert!(RomHeader::parse(b"KURUKURU", b"AK R", b"01", 0).is_err());
+    }
+
+    #[test]
+    fn region_is_the_last_letter_of_the_game_code() {
+        let region =
+            |game_code: &[u8]| RomHeader::parse(b"", game_code, b"01", 0).unwrap().region();
+
+        assert_eq!(region(b"AKRJ"), Region::Japan);
+        assert_eq!(region(b"AKRE"), Region::Usa);
+        assert_eq!(region(b"AKRP"), Region::Europe);
+        assert_eq!(region(b"AKRX"), Region::Other('X'));
     }
 }
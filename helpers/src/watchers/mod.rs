@@ -1,7 +1,8 @@
-use bytemuck::CheckedBitPattern;
 use once_cell::unsync::OnceCell;
 use std::error::Error;
 
+pub mod emulators;
+#[cfg(feature = "gba")]
 pub mod gba;
 pub mod pointer_path;
 pub mod unity;
@@ -33,14 +34,17 @@ impl<T: Copy> ValueGetter<T> for T {
 /// read a value from memory), and invalidate it at the end of the tick. During the tick, you can
 /// then observe how that value changed from the previous tick, and act on that behaviour (e.g.
 /// value went from false -> true, I should do something, i.e. start/pause/split the timer)
-pub struct Watcher<'a, T: Copy> {
+///
+/// Values are cloned out of the cache, so for collections (e.g. a `Vec` read from a unity `List`)
+/// prefer reading `current()` once per tick.
+pub struct Watcher<'a, T: Clone> {
     source: Box<dyn ValueGetter<T> + 'a>,
     current: OnceCell<T>,
     old: Option<T>,
     default: Option<T>,
 }
 
-impl<'a, T: Copy> Watcher<'a, T> {
+impl<'a, T: Clone> Watcher<'a, T> {
     pub fn new(source: Box<dyn ValueGetter<T> + 'a>) -> Self {
         Self {
             source,
@@ -62,12 +66,12 @@ impl<'a, T: Copy> Watcher<'a, T> {
                 };
 
                 // Only return the error we got if we have no default
-                match self.default {
-                    Some(default) => Ok(default),
+                match &self.default {
+                    Some(default) => Ok(default.clone()),
                     None => Err(err),
                 }
             })
-            .copied()
+            .cloned()
     }
 
     /// Retrieve the previous value for this watcher.
@@ -77,7 +81,7 @@ impl<'a, T: Copy> Watcher<'a, T> {
     ///
     /// This returns `None` if there's no value to retrieve, otherwise returns `Some` if it can.
     pub fn old(&self) -> Option<T> {
-        self.old
+        self.old.clone()
     }
 
     /// Invalidate the watcher. This moves the value of `current` into `old`, and empties the cache
@@ -88,7 +92,7 @@ impl<'a, T: Copy> Watcher<'a, T> {
         // Is this desirable?
         // None of my code depends on this behaviour at the moment, since everything is read on
         // every tick anyway.
-        self.old = self.current.get().copied();
+        self.old = self.current.get().cloned();
         self.current = OnceCell::new();
     }
 
@@ -115,18 +119,18 @@ impl<T: Copy + 'static> Watcher<'_, T> {
     }
 }
 
-impl<'a, T: Copy + PartialEq> Watcher<'a, T> {
+impl<'a, T: Clone + PartialEq> Watcher<'a, T> {
     /// Simply tells you if the value changed. Requires reading the value from current, so this can
     /// return an Err.
     pub fn changed(&self) -> Result<bool, Box<dyn Error>> {
-        match self.old {
+        match &self.old {
             None => Ok(false),
-            Some(old) => Ok(old != self.current()?),
+            Some(old) => Ok(*old != self.current()?),
         }
     }
 }
 
-impl<'a, T: CheckedBitPattern + Default> Watcher<'a, T> {
+impl<'a, T: Clone + Default> Watcher<'a, T> {
     /// Use the default value of `T` for the default when `current()` fails to retrieve a new value.
     ///
     /// See `default_given` for more documentation.
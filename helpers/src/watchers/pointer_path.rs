@@ -6,30 +6,110 @@ use std::error::Error;
 use std::fmt::Display;
 use std::iter::once;
 
+/// The byte order values are stored in, in whatever we're reading from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// Decode an unsigned integer of up to 8 bytes, e.g. a pointer.
+    pub fn decode_unsigned(self, bytes: &[u8]) -> u64 {
+        let push_byte = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+        match self {
+            Endian::Big => bytes.iter().fold(0, push_byte),
+            Endian::Little => bytes.iter().rev().fold(0, push_byte),
+        }
+    }
+}
+
 /// A PointerPathReadable is something that can read a value by pointer path.
 ///
 /// The two cases we deal with primarily are Process (the generic asr struct for a process) and
-/// Emulator (the struct that deals strictly with emulators, with various builtins).
+/// Emulator (the struct that deals strictly with emulators, with various builtins). See
+/// `watchers::emulators` for the consoles we support.
+///
+/// Implementors only need to say how to read a pointer at an address, and how to read values (see
+/// `ReadValue`), and the walk down the path is shared.
 ///
 /// Our code should not assume it has a particular kind of readable object.
 pub trait PointerPathReadable {
-    fn read_pointer_path<T: CheckedBitPattern>(
+    /// Read a pointer at the address. Readables that know their own pointer size (e.g. emulators)
+    /// may ignore `pointer_size`.
+    fn read_pointer(
+        &self,
+        address: Address,
+        pointer_size: PointerSize,
+    ) -> Result<Address, Box<dyn Error>>;
+
+    /// The byte order of the memory we're reading, e.g. big endian for a GameCube.
+    fn endian(&self) -> Endian {
+        Endian::Little
+    }
+
+    /// Follow every offset but the last as a pointer, giving the address of the value at the last
+    /// offset.
+    fn resolve_pointer_path(
         &self,
         address: impl Into<Address>,
         pointer_size: PointerSize,
         path: &[u64],
-    ) -> Result<T, Box<dyn Error>>;
-}
+    ) -> Result<Address, Box<dyn Error>> {
+        let mut address = address.into();
+        let (&last, path) = path.split_last().unwrap_or((&0, &[]));
+        for &offset in path {
+            address = self.read_pointer(address + offset, pointer_size)?;
+        }
 
-impl PointerPathReadable for Process {
-    fn read_pointer_path<T: CheckedBitPattern>(
+        Ok(address + last)
+    }
+
+    /// Follow every offset but the last as a pointer, then read the value at the last offset.
+    fn read_pointer_path<T>(
         &self,
         address: impl Into<Address>,
         pointer_size: PointerSize,
         path: &[u64],
-    ) -> Result<T, Box<dyn Error>> {
-        self.read_pointer_path::<T>(address, pointer_size, path)
-            .map_err(|_| SimpleError::from("unable to read value from pointer path").into())
+    ) -> Result<T, Box<dyn Error>>
+    where
+        Self: ReadValue<T>,
+    {
+        let address = self.resolve_pointer_path(address, pointer_size, path)?;
+        self.read_value(address)
+    }
+}
+
+/// Reads a `T` from a readable, converted to native byte order.
+///
+/// This is its own trait so each readable can ask for what it needs: a little endian readable can
+/// read any `CheckedBitPattern` as it is in memory, while a big endian one needs `FromEndian` to
+/// swap it, which only numbers (and arrays of them) have - swapping a whole struct would scramble
+/// its fields.
+pub trait ReadValue<T>: PointerPathReadable {
+    fn read_value(&self, address: Address) -> Result<T, Box<dyn Error>>;
+}
+
+impl PointerPathReadable for Process {
+    fn read_pointer(
+        &self,
+        address: Address,
+        pointer_size: PointerSize,
+    ) -> Result<Address, Box<dyn Error>> {
+        let mut bytes = [0; 8];
+        let bytes = &mut bytes[..pointer_size as usize];
+        self.read_into_buf(address, bytes)
+            .map_err(|_| SimpleError::from(&format!("unable to read pointer at {address}")))?;
+
+        Ok(Address::new(self.endian().decode_unsigned(bytes)))
+    }
+}
+
+impl<T: CheckedBitPattern> ReadValue<T> for Process {
+    fn read_value(&self, address: Address) -> Result<T, Box<dyn Error>> {
+        self.read::<T>(address)
+            .map_err(|_| SimpleError::from(&format!("unable to read value at {address}")).into())
     }
 }
 
@@ -78,18 +158,23 @@ impl<'a, TReadable: PointerPathReadable> PointerPath<'a, TReadable> {
         }
     }
 
-    /// Reads the value pointed to by the pointer path.
-    ///
-    /// The actual work for dereferencing and doing the reading is handled by the `readable`.
-    pub fn read<T: CheckedBitPattern>(&self) -> Result<T, Box<dyn Error>> {
-        let valid_path = if !self.path.is_empty() {
+    fn valid_path(&self) -> &[u64] {
+        if !self.path.is_empty() {
             &self.path
         } else {
-            &vec![0x0]
-        };
+            &[0x0]
+        }
+    }
 
+    /// Reads the value pointed to by the pointer path, in native byte order.
+    ///
+    /// The actual work for dereferencing and doing the reading is handled by the `readable`.
+    pub fn read<T>(&self) -> Result<T, Box<dyn Error>>
+    where
+        TReadable: ReadValue<T>,
+    {
         self.readable
-            .read_pointer_path(self.base_address, self.pointer_size, valid_path)
+            .read_pointer_path(self.base_address, self.pointer_size, self.valid_path())
             .map_err(|e| SimpleError::wrap(format!("failed to read pointer path {self}"), e).into())
     }
 
@@ -124,12 +209,15 @@ impl<'a, TReadable: PointerPathReadable> PointerPath<'a, TReadable> {
         }
     }
 
-    pub fn child_watcher<T: CheckedBitPattern>(&self, path: impl Into<Vec<u64>>) -> Watcher<'a, T> {
+    pub fn child_watcher<T: CheckedBitPattern>(&self, path: impl Into<Vec<u64>>) -> Watcher<'a, T>
+    where
+        TReadable: ReadValue<T>,
+    {
         self.child(path).into()
     }
 }
 
-impl<'a, TReadable: PointerPathReadable, T: CheckedBitPattern> ValueGetter<T>
+impl<'a, T: CheckedBitPattern, TReadable: ReadValue<T>> ValueGetter<T>
     for PointerPath<'a, TReadable>
 {
     fn get(&self) -> Result<T, Box<dyn Error>> {
@@ -137,7 +225,7 @@ impl<'a, TReadable: PointerPathReadable, T: CheckedBitPattern> ValueGetter<T>
     }
 }
 
-impl<'a, TReadable: PointerPathReadable, T: CheckedBitPattern> From<PointerPath<'a, TReadable>>
+impl<'a, T: CheckedBitPattern, TReadable: ReadValue<T>> From<PointerPath<'a, TReadable>>
     for Watcher<'a, T>
 {
     fn from(value: PointerPath<'a, TReadable>) -> Self {
@@ -0,0 +1,494 @@
+use crate::error::SimpleError;
+use crate::watchers::unity::{UnityImage, UnityPointerPath};
+use crate::watchers::{ValueGetter, Watcher};
+use asr::{Address, Address32, Address64, PointerSize, Process};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Anything bigger than this is almost certainly garbage (e.g. a stale pointer), so we bail
+/// instead of allocating for it.
+const MAX_ELEMENTS: u64 = 0x10000;
+const MAX_STRING_LENGTH: u64 = 0x10000;
+
+/// Old mono (the .NET 3.5 profile) marks used slots with the high bit of the hash code.
+const DICTIONARY_SLOT_USED: u32 = 0x80000000;
+
+fn pointer_bytes(pointer_size: PointerSize) -> u64 {
+    match pointer_size {
+        PointerSize::Bit64 => 8,
+        PointerSize::Bit32 => 4,
+        PointerSize::Bit16 => 2,
+    }
+}
+
+fn align_up(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
+
+/// Offsets into managed objects that are fixed by the runtime, rather than being fields we can
+/// look up. Both mono and il2cpp objects start with two pointers (the class and the monitor).
+struct ObjectLayout {
+    string_length: u64,
+    string_chars: u64,
+    array_length: u64,
+    array_data: u64,
+}
+
+impl ObjectLayout {
+    fn new(pointer_size: PointerSize) -> Self {
+        let header = 2 * pointer_bytes(pointer_size);
+
+        ObjectLayout {
+            string_length: header,
+            string_chars: header + 0x4,
+            // the array bounds pointer comes before the length
+            array_length: header + pointer_bytes(pointer_size),
+            array_data: header + 2 * pointer_bytes(pointer_size),
+        }
+    }
+}
+
+/// Something that can be an element of a managed collection.
+///
+/// Value types are read in place, reference types (`String`, or an object's address) are read
+/// through the pointer stored in the collection.
+pub trait UnityElement: Sized {
+    /// The size the element takes up in the collection, which is also its alignment.
+    fn size(pointer_size: PointerSize) -> u64;
+
+    fn read(
+        process: &Process,
+        address: Address,
+        pointer_size: PointerSize,
+    ) -> Result<Self, Box<dyn Error>>;
+}
+
+macro_rules! impl_unity_element {
+    ($($ty:ty),+) => {
+        $(impl UnityElement for $ty {
+            fn size(_pointer_size: PointerSize) -> u64 {
+                size_of::<$ty>() as u64
+            }
+
+            fn read(
+                process: &Process,
+                address: Address,
+                _pointer_size: PointerSize,
+            ) -> Result<Self, Box<dyn Error>> {
+                process.read::<$ty>(address).map_err(|_| {
+                    SimpleError::from(&format!(
+                        "failed to read {} element at {address}",
+                        stringify!($ty)
+                    ))
+                    .into()
+                })
+            }
+        })+
+    };
+}
+
+impl_unity_element!(bool, u8, i8, u16, i16, u32, i32, u64, i64, f32, f64, Address32, Address64);
+
+/// A reference to some other managed object, read as its address.
+impl UnityElement for Address {
+    fn size(pointer_size: PointerSize) -> u64 {
+        pointer_bytes(pointer_size)
+    }
+
+    fn read(
+        process: &Process,
+        address: Address,
+        pointer_size: PointerSize,
+    ) -> Result<Self, Box<dyn Error>> {
+        process
+            .read_pointer(address, pointer_size)
+            .map_err(|_| SimpleError::from(&format!("failed to read pointer at {address}")).into())
+    }
+}
+
+impl UnityElement for String {
+    fn size(pointer_size: PointerSize) -> u64 {
+        pointer_bytes(pointer_size)
+    }
+
+    fn read(
+        process: &Process,
+        address: Address,
+        pointer_size: PointerSize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let string = Address::read(process, address, pointer_size)?;
+        read_string(process, string, pointer_size)
+    }
+}
+
+/// Read a `System.String` object. Strings aren't null-terminated, so this uses the length stored
+/// in the object.
+pub fn read_string(
+    process: &Process,
+    string: Address,
+    pointer_size: PointerSize,
+) -> Result<String, Box<dyn Error>> {
+    if string.is_null() {
+        return Err(SimpleError::from("string is null").into());
+    }
+
+    let layout = ObjectLayout::new(pointer_size);
+    let length = process
+        .read::<i32>(string + layout.string_length)
+        .map_err(|_| SimpleError::from("failed to read string length"))? as u64;
+
+    if length > MAX_STRING_LENGTH {
+        return Err(SimpleError::from(&format!("string length {length} is too long")).into());
+    }
+
+    let mut buf = vec![0u16; length as usize];
+    process
+        .read_into_slice(string + layout.string_chars, &mut buf)
+        .map_err(|_| SimpleError::from("failed to read string contents"))?;
+
+    Ok(String::from_utf16_lossy(&buf))
+}
+
+fn read_elements<T: UnityElement>(
+    process: &Process,
+    data: Address,
+    count: u64,
+    pointer_size: PointerSize,
+) -> Result<Vec<T>, Box<dyn Error>> {
+    if count > MAX_ELEMENTS {
+        return Err(SimpleError::from(&format!("{count} elements is too many")).into());
+    }
+
+    let size = T::size(pointer_size);
+    (0..count)
+        .map(|i| T::read(process, data + i * size, pointer_size))
+        .collect()
+}
+
+/// Read a `T[]` object.
+fn read_array<T: UnityElement>(
+    process: &Process,
+    array: Address,
+    pointer_size: PointerSize,
+) -> Result<Vec<T>, Box<dyn Error>> {
+    let (data, length) = array_data(process, array, pointer_size)?;
+    read_elements(process, data, length, pointer_size)
+}
+
+fn array_data(
+    process: &Process,
+    array: Address,
+    pointer_size: PointerSize,
+) -> Result<(Address, u64), Box<dyn Error>> {
+    if array.is_null() {
+        return Err(SimpleError::from("array is null").into());
+    }
+
+    let layout = ObjectLayout::new(pointer_size);
+    let length = match pointer_size {
+        PointerSize::Bit64 => process.read::<u64>(array + layout.array_length),
+        _ => process
+            .read::<u32>(array + layout.array_length)
+            .map(|l| l as u64),
+    }
+    .map_err(|_| SimpleError::from("failed to read array length"))?;
+
+    Ok((array + layout.array_data, length))
+}
+
+/// Reads a `System.String` at the end of a path.
+pub struct UnityString<'a> {
+    path: UnityPointerPath<'a>,
+}
+
+impl ValueGetter<String> for UnityString<'_> {
+    fn get(&self) -> Result<String, Box<dyn Error>> {
+        let pointer_size = self.path.unity.pointer_size();
+        read_string(self.path.process, self.path.read_object()?, pointer_size)
+    }
+}
+
+/// Reads a `T[]` at the end of a path.
+pub struct UnityArray<'a, T> {
+    _phantom: PhantomData<T>,
+    path: UnityPointerPath<'a>,
+}
+
+impl<T: UnityElement> ValueGetter<Vec<T>> for UnityArray<'_, T> {
+    fn get(&self) -> Result<Vec<T>, Box<dyn Error>> {
+        let pointer_size = self.path.unity.pointer_size();
+        read_array(self.path.process, self.path.read_object()?, pointer_size)
+    }
+}
+
+/// Reads a `List<T>` at the end of a path.
+pub struct UnityList<'a, T> {
+    _phantom: PhantomData<T>,
+    path: UnityPointerPath<'a>,
+    // (_items, _size), looked up the first time we see the list
+    offsets: Cell<Option<(u32, u32)>>,
+}
+
+impl<T: UnityElement> ValueGetter<Vec<T>> for UnityList<'_, T> {
+    fn get(&self) -> Result<Vec<T>, Box<dyn Error>> {
+        let unity = &self.path.unity;
+        let process = self.path.process;
+        let pointer_size = unity.pointer_size();
+        let list = self.path.read_object()?;
+
+        let (items_offset, size_offset) = match self.offsets.get() {
+            Some(offsets) => offsets,
+            None => {
+                let offsets = (
+                    unity.field_offset_of_object(list, "_items")?,
+                    unity.field_offset_of_object(list, "_size")?,
+                );
+                self.offsets.set(Some(offsets));
+                offsets
+            }
+        };
+
+        let items = process
+            .read_pointer(list + items_offset, pointer_size)
+            .map_err(|_| SimpleError::from("failed to read list items"))?;
+        let size = process
+            .read::<i32>(list + size_offset)
+            .map_err(|_| SimpleError::from("failed to read list size"))?;
+
+        let (data, capacity) = array_data(process, items, pointer_size)?;
+        if size < 0 || size as u64 > capacity {
+            return Err(SimpleError::from(&format!(
+                "list size {size} doesn't fit in its capacity {capacity}"
+            ))
+            .into());
+        }
+
+        read_elements(process, data, size as u64, pointer_size)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DictionaryLayout {
+    /// .NET 4.x and later: an array of `Entry { hashCode, next, key, value }`.
+    Entries { entries: u32, count: u32 },
+    /// The old mono .NET 3.5 profile: separate arrays for keys, values and links.
+    Slots {
+        key_slots: u32,
+        value_slots: u32,
+        link_slots: u32,
+        touched_slots: u32,
+    },
+}
+
+impl DictionaryLayout {
+    fn find(unity: &UnityImage, dictionary: Address) -> Result<Self, Box<dyn Error>> {
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| unity.field_offset_of_object(dictionary, name).ok())
+        };
+
+        if let (Some(entries), Some(count)) =
+            (field(&["entries", "_entries"]), field(&["count", "_count"]))
+        {
+            return Ok(DictionaryLayout::Entries { entries, count });
+        }
+
+        match (
+            field(&["keySlots"]),
+            field(&["valueSlots"]),
+            field(&["linkSlots"]),
+            field(&["touchedSlots"]),
+        ) {
+            (Some(key_slots), Some(value_slots), Some(link_slots), Some(touched_slots)) => {
+                Ok(DictionaryLayout::Slots {
+                    key_slots,
+                    value_slots,
+                    link_slots,
+                    touched_slots,
+                })
+            }
+            _ => Err(SimpleError::from("unrecognised dictionary layout").into()),
+        }
+    }
+}
+
+/// Reads a `Dictionary<K, V>` at the end of a path.
+pub struct UnityDictionary<'a, K, V> {
+    _phantom: PhantomData<(K, V)>,
+    path: UnityPointerPath<'a>,
+    layout: Cell<Option<DictionaryLayout>>,
+}
+
+impl<K: UnityElement + Eq + Hash, V: UnityElement> ValueGetter<HashMap<K, V>>
+    for UnityDictionary<'_, K, V>
+{
+    fn get(&self) -> Result<HashMap<K, V>, Box<dyn Error>> {
+        let unity = &self.path.unity;
+        let process = self.path.process;
+        let pointer_size = unity.pointer_size();
+        let dictionary = self.path.read_object()?;
+
+        let layout = match self.layout.get() {
+            Some(layout) => layout,
+            None => {
+                let layout = DictionaryLayout::find(unity, dictionary)?;
+                self.layout.set(Some(layout));
+                layout
+            }
+        };
+
+        let read_field_pointer = |offset: u32| {
+            process
+                .read_pointer(dictionary + offset, pointer_size)
+                .map_err(|_| SimpleError::from("failed to read dictionary field"))
+        };
+        let read_field_i32 = |offset: u32| {
+            process
+                .read::<i32>(dictionary + offset)
+                .map_err(|_| SimpleError::from("failed to read dictionary field"))
+        };
+
+        let mut map = HashMap::new();
+
+        match layout {
+            DictionaryLayout::Entries { entries, count } => {
+                let (data, capacity) =
+                    array_data(process, read_field_pointer(entries)?, pointer_size)?;
+                let count = read_field_i32(count)?;
+                if count < 0 || count as u64 > capacity.min(MAX_ELEMENTS) {
+                    return Err(SimpleError::from(&format!(
+                        "dictionary count {count} doesn't fit in its capacity {capacity}"
+                    ))
+                    .into());
+                }
+
+                let key_size = K::size(pointer_size);
+                let value_size = V::size(pointer_size);
+                let key_offset = 0x8;
+                let value_offset = align_up(key_offset + key_size, value_size);
+                let entry_size =
+                    align_up(value_offset + value_size, key_size.max(value_size).max(4));
+
+                for i in 0..count as u64 {
+                    let entry = data + i * entry_size;
+                    let hash_code = process
+                        .read::<i32>(entry)
+                        .map_err(|_| SimpleError::from("failed to read dictionary entry"))?;
+                    // free entries have a negative hash code
+                    if hash_code < 0 {
+                        continue;
+                    }
+
+                    map.insert(
+                        K::read(process, entry + key_offset, pointer_size)?,
+                        V::read(process, entry + value_offset, pointer_size)?,
+                    );
+                }
+            }
+            DictionaryLayout::Slots {
+                key_slots,
+                value_slots,
+                link_slots,
+                touched_slots,
+            } => {
+                let touched = read_field_i32(touched_slots)?;
+                if touched < 0 || touched as u64 > MAX_ELEMENTS {
+                    return Err(SimpleError::from(&format!(
+                        "dictionary slot count {touched} is out of range"
+                    ))
+                    .into());
+                }
+
+                let (keys, _) = array_data(process, read_field_pointer(key_slots)?, pointer_size)?;
+                let (values, _) =
+                    array_data(process, read_field_pointer(value_slots)?, pointer_size)?;
+                // Link { int HashCode; int Next; }
+                let (links, _) =
+                    array_data(process, read_field_pointer(link_slots)?, pointer_size)?;
+
+                for i in 0..touched as u64 {
+                    let hash_code = process
+                        .read::<u32>(links + i * 0x8)
+                        .map_err(|_| SimpleError::from("failed to read dictionary link"))?;
+                    if hash_code & DICTIONARY_SLOT_USED == 0 {
+                        continue;
+                    }
+
+                    map.insert(
+                        K::read(process, keys + i * K::size(pointer_size), pointer_size)?,
+                        V::read(process, values + i * V::size(pointer_size), pointer_size)?,
+                    );
+                }
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+impl<'a> UnityPointerPath<'a> {
+    /// Read the `System.String` this path points to.
+    pub fn string(self) -> UnityString<'a> {
+        UnityString { path: self }
+    }
+
+    /// Read the `T[]` this path points to.
+    pub fn array<T: UnityElement>(self) -> UnityArray<'a, T> {
+        UnityArray {
+            _phantom: PhantomData,
+            path: self,
+        }
+    }
+
+    /// Read the `List<T>` this path points to.
+    pub fn list<T: UnityElement>(self) -> UnityList<'a, T> {
+        UnityList {
+            _phantom: PhantomData,
+            path: self,
+            offsets: Cell::new(None),
+        }
+    }
+
+    /// Read the `Dictionary<K, V>` this path points to.
+    pub fn dictionary<K: UnityElement + Eq + Hash, V: UnityElement>(
+        self,
+    ) -> UnityDictionary<'a, K, V> {
+        UnityDictionary {
+            _phantom: PhantomData,
+            path: self,
+            layout: Cell::new(None),
+        }
+    }
+}
+
+impl<'a> From<UnityString<'a>> for Watcher<'a, String> {
+    fn from(value: UnityString<'a>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
+
+impl<'a, T: UnityElement + Clone + 'a> From<UnityArray<'a, T>> for Watcher<'a, Vec<T>> {
+    fn from(value: UnityArray<'a, T>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
+
+impl<'a, T: UnityElement + Clone + 'a> From<UnityList<'a, T>> for Watcher<'a, Vec<T>> {
+    fn from(value: UnityList<'a, T>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
+
+impl<'a, K, V> From<UnityDictionary<'a, K, V>> for Watcher<'a, HashMap<K, V>>
+where
+    K: UnityElement + Eq + Hash + Clone + 'a,
+    V: UnityElement + Clone + 'a,
+{
+    fn from(value: UnityDictionary<'a, K, V>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
@@ -0,0 +1,251 @@
+use crate::error::SimpleError;
+use crate::info;
+use crate::watchers::unity::{UnityBackend, UnityImage};
+use asr::{Address, PointerSize, Process};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// FIELD_ATTRIBUTE_* from ECMA-335, in the attrs of the field's type
+const FIELD_ATTRIBUTE_STATIC: u16 = 0x10;
+const FIELD_ATTRIBUTE_LITERAL: u16 = 0x40;
+
+#[derive(Clone, Debug)]
+pub struct FieldDump {
+    pub name: String,
+    pub type_name: String,
+    /// For static fields, this is the offset into the class's static data, not the object.
+    pub offset: Option<u32>,
+    pub is_static: bool,
+    /// Constants don't live anywhere in memory.
+    pub is_const: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClassDump {
+    pub namespace: String,
+    pub name: String,
+    pub parent: Option<String>,
+    /// Only the fields declared on this class, not the inherited ones.
+    pub fields: Vec<FieldDump>,
+}
+
+impl Display for FieldDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let offset = match self.offset {
+            Some(offset) if !self.is_const => format!("0x{offset:03X}"),
+            _ => "  -  ".to_owned(),
+        };
+        let modifier = if self.is_const {
+            "const "
+        } else if self.is_static {
+            "static "
+        } else {
+            ""
+        };
+
+        write!(
+            f,
+            "/* {offset} */ {modifier}{} {};",
+            self.type_name, self.name
+        )
+    }
+}
+
+impl Display for ClassDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.namespace.is_empty() {
+            writeln!(f, "// namespace {}", self.namespace)?;
+        }
+        write!(f, "class {}", self.name)?;
+        if let Some(parent) = &self.parent {
+            write!(f, " : {parent}")?;
+        }
+        writeln!(f, "\n{{")?;
+
+        // statics first, like you'd usually write them
+        let (statics, mut instance): (Vec<_>, Vec<_>) = self
+            .fields
+            .iter()
+            .partition(|field| field.is_static || field.is_const);
+        for field in statics {
+            writeln!(f, "    {field}")?;
+        }
+        instance.sort_by_key(|field| field.offset);
+        for field in instance {
+            writeln!(f, "    {field}")?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+// mono and il2cpp classes have the same API, but no shared trait, hence the macro.
+// `$type_offset` is where the field's type pointer is in the field struct:
+// `MonoClassField` starts with the type, il2cpp's `FieldInfo` starts with the name.
+macro_rules! dump_classes {
+    ($process:expr, $module:expr, $image:expr, $type_offset:expr) => {{
+        let process = $process;
+        let module = $module;
+        let pointer_size = module.get_pointer_size();
+
+        let classes = $image.classes(process, module).collect::<Vec<_>>();
+
+        // so we can give field types a name, at least for classes in this image
+        let class_names = classes
+            .iter()
+            .filter_map(|class| {
+                let name = class.get_name::<128>(process, module).ok()?;
+                Some((class.address(), name.validate_utf8().ok()?.to_owned()))
+            })
+            .collect::<HashMap<_, _>>();
+
+        classes
+            .iter()
+            .filter_map(|class| {
+                let name = class_names.get(&class.address())?.clone();
+                let namespace = class
+                    .get_name_space::<128>(process, module)
+                    .ok()
+                    .and_then(|ns| ns.validate_utf8().ok().map(str::to_owned))
+                    .unwrap_or_default();
+                let parent = class.get_parent(process, module).and_then(|parent| {
+                    let name = parent.get_name::<128>(process, module).ok()?;
+                    name.validate_utf8().ok().map(str::to_owned)
+                });
+
+                let fields = class
+                    .fields(process, module)
+                    .filter_map(|field| {
+                        let name = field.get_name::<128>(process, module).ok()?;
+                        let name = name.validate_utf8().ok()?.to_owned();
+                        let field_type = process
+                            .read_pointer(field.address() + $type_offset, pointer_size)
+                            .ok()?;
+                        let info = FieldType::read(process, field_type, pointer_size)?;
+
+                        Some(FieldDump {
+                            name,
+                            type_name: info.name(process, pointer_size, &class_names),
+                            offset: field.get_offset(process, module),
+                            is_static: info.attrs & FIELD_ATTRIBUTE_STATIC != 0,
+                            is_const: info.attrs & FIELD_ATTRIBUTE_LITERAL != 0,
+                        })
+                    })
+                    .collect();
+
+                Some(ClassDump {
+                    namespace,
+                    name,
+                    parent,
+                    fields,
+                })
+            })
+            .collect::<Vec<_>>()
+    }};
+}
+
+/// The bits of a `MonoType`/`Il2CppType` we care about. They share a layout: a pointer-sized data
+/// union, then a bitfield with the attrs in the low 16 bits and the type enum in the next 8.
+struct FieldType {
+    data: Address,
+    attrs: u16,
+    kind: u8,
+}
+
+impl FieldType {
+    fn read(process: &Process, address: Address, pointer_size: PointerSize) -> Option<Self> {
+        let data = process.read_pointer(address, pointer_size).ok()?;
+        let bits = process.read::<u32>(address + pointer_size as u64).ok()?;
+
+        Some(FieldType {
+            data,
+            attrs: bits as u16,
+            kind: (bits >> 16) as u8,
+        })
+    }
+
+    fn name(
+        &self,
+        process: &Process,
+        pointer_size: PointerSize,
+        class_names: &HashMap<Address, String>,
+    ) -> String {
+        let class_name = |class: Address, fallback: &str| {
+            class_names
+                .get(&class)
+                .cloned()
+                .unwrap_or_else(|| fallback.to_owned())
+        };
+
+        // MONO_TYPE_* / IL2CPP_TYPE_*, they're the same
+        match self.kind {
+            0x01 => "void".to_owned(),
+            0x02 => "bool".to_owned(),
+            0x03 => "char".to_owned(),
+            0x04 => "sbyte".to_owned(),
+            0x05 => "byte".to_owned(),
+            0x06 => "short".to_owned(),
+            0x07 => "ushort".to_owned(),
+            0x08 => "int".to_owned(),
+            0x09 => "uint".to_owned(),
+            0x0A => "long".to_owned(),
+            0x0B => "ulong".to_owned(),
+            0x0C => "float".to_owned(),
+            0x0D => "double".to_owned(),
+            0x0E => "string".to_owned(),
+            0x0F => "void*".to_owned(),
+            0x11 => class_name(self.data, "struct"),
+            0x12 => class_name(self.data, "object"),
+            0x13 | 0x1E => "T".to_owned(),
+            0x14 => "Array".to_owned(),
+            // for mono this points at a MonoGenericClass, which starts with the generic definition
+            0x15 => process
+                .read_pointer(self.data, pointer_size)
+                .map(|definition| class_name(definition, "generic"))
+                .unwrap_or_else(|_| "generic".to_owned()),
+            0x18 => "IntPtr".to_owned(),
+            0x19 => "UIntPtr".to_owned(),
+            0x1C => "object".to_owned(),
+            // for mono this is the element class
+            0x1D => format!("{}[]", class_name(self.data, "object")),
+            kind => format!("/* type 0x{kind:02X} */ object"),
+        }
+    }
+}
+
+impl UnityImage<'_> {
+    /// Read every class in the image, with its parent and the fields it declares.
+    ///
+    /// Field types are only named for classes in this image, anything else shows up as `object`
+    /// or `struct`.
+    pub fn dump_classes(&self) -> Vec<ClassDump> {
+        match &self.backend {
+            UnityBackend::Mono { module, image } => {
+                dump_classes!(self.process, module.as_ref(), image, 0)
+            }
+            UnityBackend::Il2Cpp { module, image } => dump_classes!(
+                self.process,
+                module.as_ref(),
+                image,
+                module.get_pointer_size() as u64
+            ),
+        }
+    }
+
+    /// Print every class in the image as C#-ish pseudo-source, for finding field names and
+    /// offsets for a new game.
+    pub fn dump(&self) -> Result<(), Box<dyn Error>> {
+        let classes = self.dump_classes();
+        if classes.is_empty() {
+            return Err(SimpleError::from("no classes found in the image").into());
+        }
+
+        info!("// {} classes", classes.len());
+        for class in classes {
+            info!("{class}\n");
+        }
+
+        Ok(())
+    }
+}
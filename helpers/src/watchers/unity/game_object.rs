@@ -0,0 +1,413 @@
+use crate::error::SimpleError;
+use crate::watchers::unity::scenes::SceneTarget;
+use crate::watchers::{ValueGetter, Watcher};
+use asr::game_engine::unity::scene_manager::{CppGameObject, SceneManager, Transform};
+use asr::{Address, Process};
+use std::cell::Cell;
+use std::error::Error;
+use std::ops::{Add, Mul, Sub};
+use std::rc::Rc;
+
+/// How many parents we walk up when computing a world position before assuming something's wrong.
+const MAX_HIERARCHY_DEPTH: usize = 256;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    fn cross(self, other: Vector3) -> Vector3 {
+        Vector3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn distance(self, other: Vector3) -> f32 {
+        let d = self - other;
+        (d.x * d.x + d.y * d.y + d.z * d.z).sqrt()
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, rhs: Vector3) -> Vector3 {
+        Vector3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, rhs: Vector3) -> Vector3 {
+        Vector3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: f32) -> Vector3 {
+        Vector3 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+/// Where the local transforms live in the native (C++) transform.
+///
+/// A native `Transform` points at the `TransformHierarchy` it's part of, and its index in it. The
+/// hierarchy has an array of local position/rotation/scale (one `TransformX` per transform, each
+/// padded to 16 bytes per part), and an array of parent indices.
+///
+/// The defaults are for 64 bit Unity 2019 and up, which is everything we've needed so far. Other
+/// versions may lay this out differently.
+#[derive(Clone, Copy, Debug)]
+pub struct TransformOffsets {
+    pub hierarchy: u64,
+    pub index: u64,
+    pub local_transforms: u64,
+    pub parent_indices: u64,
+}
+
+impl Default for TransformOffsets {
+    fn default() -> Self {
+        TransformOffsets {
+            hierarchy: 0x38,
+            index: 0x40,
+            local_transforms: 0x18,
+            parent_indices: 0x20,
+        }
+    }
+}
+
+/// Position, rotation (quaternion, xyzw) and scale, as Unity stores them for each transform.
+#[derive(Clone, Copy, Debug)]
+struct TransformX {
+    position: Vector3,
+    rotation: [f32; 4],
+    scale: Vector3,
+}
+
+impl TransformX {
+    fn read(process: &Process, address: Address) -> Result<Self, Box<dyn Error>> {
+        let raw = process
+            .read::<[f32; 12]>(address)
+            .map_err(|_| SimpleError::from(&format!("couldnt read transform at {address}")))?;
+
+        Ok(TransformX {
+            position: Vector3 {
+                x: raw[0],
+                y: raw[1],
+                z: raw[2],
+            },
+            rotation: [raw[4], raw[5], raw[6], raw[7]],
+            scale: Vector3 {
+                x: raw[8],
+                y: raw[9],
+                z: raw[10],
+            },
+        })
+    }
+
+    /// Apply this transform to a point in its local space.
+    fn apply(&self, point: Vector3) -> Vector3 {
+        let scaled = Vector3 {
+            x: point.x * self.scale.x,
+            y: point.y * self.scale.y,
+            z: point.z * self.scale.z,
+        };
+
+        let [x, y, z, w] = self.rotation;
+        let axis = Vector3 { x, y, z };
+        let t = axis.cross(scaled) * 2.0;
+        let rotated = scaled + t * w + axis.cross(t);
+
+        rotated + self.position
+    }
+}
+
+/// A game object found by its path in a scene, that can be asked about its children, components
+/// and transform.
+///
+/// Like `GameObjectActivePath`, the object is cached until its scene is reloaded. An object that
+/// doesn't exist (yet, or anymore) is an error from everything but `exists`.
+///
+/// Use `watch` to turn a query into a `Watcher` source, e.g. to split when an object spawns:
+///
+/// ```ignore
+/// let boss_spawned: Watcher<bool> = GameObjectQuery::new(process, scene_manager, "level_1", "Enemies", ["Boss"])
+///     .watch(|boss| Ok(boss.exists()))
+///     .into();
+/// ```
+pub struct GameObjectQuery<'a> {
+    process: &'a Process,
+    scene_manager: Rc<SceneManager>,
+
+    scene: SceneTarget,
+    root_object_name: String,
+    path: Vec<String>,
+    transform_offsets: TransformOffsets,
+
+    // along with the address of the scene it was found in, so a reloaded scene is searched again
+    cached_transform: Cell<Option<(Address, Transform)>>,
+}
+
+impl<'a> GameObjectQuery<'a> {
+    pub fn new<S: Into<String>>(
+        process: &'a Process,
+        scene_manager: Rc<SceneManager>,
+        scene: impl Into<SceneTarget>,
+        root_object_name: impl Into<String>,
+        path: impl IntoIterator<Item = S>,
+    ) -> Self {
+        GameObjectQuery {
+            process,
+            scene_manager,
+            scene: scene.into(),
+            root_object_name: root_object_name.into(),
+            path: path.into_iter().map(Into::into).collect(),
+            transform_offsets: TransformOffsets::default(),
+            cached_transform: Cell::new(None),
+        }
+    }
+
+    /// For games where the native transform isn't laid out like `TransformOffsets::default`.
+    pub fn with_transform_offsets(mut self, transform_offsets: TransformOffsets) -> Self {
+        self.transform_offsets = transform_offsets;
+        self
+    }
+
+    pub fn transform(&self) -> Result<Transform, Box<dyn Error>> {
+        let scene = self
+            .scene
+            .resolve(self.process, &self.scene_manager)
+            .inspect_err(|_| self.cached_transform.set(None))?;
+
+        if let Some((scene_address, transform)) = self.cached_transform.get() {
+            // the object may have been destroyed since, so check it's still what we found
+            if scene_address == scene.address() && self.is_still_valid(&transform) {
+                return Ok(transform);
+            }
+
+            self.cached_transform.set(None);
+        }
+
+        let path = self.path.iter().map(String::as_str).collect::<Vec<_>>();
+        let transform = scene
+            .find_transform(
+                self.process,
+                &self.scene_manager,
+                &self.root_object_name,
+                &path,
+            )
+            .map_err(|_| {
+                SimpleError::from(&format!(
+                    "couldnt find transform {}/{}",
+                    self.root_object_name,
+                    self.path.join("/")
+                ))
+            })?;
+
+        self.cached_transform
+            .set(Some((scene.address(), transform)));
+
+        Ok(transform)
+    }
+
+    /// Whether a transform we found before still looks like the object at the end of our path:
+    /// it still has a game object, with the name we searched for.
+    fn is_still_valid(&self, transform: &Transform) -> bool {
+        let expected_name = self.path.last().unwrap_or(&self.root_object_name);
+
+        transform
+            .get_game_object(self.process, &self.scene_manager)
+            .is_ok()
+            && transform
+                .get_name(self.process, &self.scene_manager)
+                .is_ok_and(|name| &name == expected_name)
+    }
+
+    pub fn game_object(&self) -> Result<CppGameObject, Box<dyn Error>> {
+        self.transform()?
+            .get_game_object(self.process, &self.scene_manager)
+            .map_err(|_| SimpleError::from("couldnt get game_object").into())
+    }
+
+    /// Whether the object can be found right now, i.e. its scene is loaded and it hasn't been
+    /// destroyed.
+    ///
+    /// This always searches the hierarchy again rather than trusting the cached transform, since
+    /// the memory of a destroyed object can still look like the object for a while.
+    pub fn exists(&self) -> bool {
+        self.cached_transform.set(None);
+        self.transform().is_ok()
+    }
+
+    pub fn is_active(&self) -> Result<bool, Box<dyn Error>> {
+        self.game_object()?
+            .is_active_in_hierarchy(self.process, &self.scene_manager)
+            .map_err(|_| SimpleError::from("couldnt get is active").into())
+    }
+
+    /// The direct children's transforms.
+    pub fn children(&self) -> Result<Vec<Transform>, Box<dyn Error>> {
+        Ok(self
+            .transform()?
+            .children(self.process, &self.scene_manager)
+            .map_err(|_| SimpleError::from("couldnt get children"))?
+            .collect())
+    }
+
+    /// The names of the direct children, e.g. to find objects that were spawned with a generated
+    /// name.
+    pub fn child_names(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .children()?
+            .iter()
+            .filter_map(|child| child.get_name(self.process, &self.scene_manager).ok())
+            .collect())
+    }
+
+    pub fn child_count(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(self.children()?.len())
+    }
+
+    /// How many of the direct children are active in the hierarchy.
+    pub fn active_child_count(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(self
+            .children()?
+            .iter()
+            .filter(|child| {
+                child
+                    .get_game_object(self.process, &self.scene_manager)
+                    .and_then(|object| {
+                        object.is_active_in_hierarchy(self.process, &self.scene_manager)
+                    })
+                    .unwrap_or(false)
+            })
+            .count())
+    }
+
+    /// The address of the component (the managed object) with the given type name.
+    pub fn component(&self, type_name: &str) -> Result<Address, Box<dyn Error>> {
+        self.transform()?
+            .get_class(self.process, &self.scene_manager, type_name)
+            .map_err(|_| SimpleError::from(&format!("couldnt find component {type_name}")).into())
+    }
+
+    pub fn has_component(&self, type_name: &str) -> Result<bool, Box<dyn Error>> {
+        // make sure a missing object is an error, and not just a missing component
+        self.transform()?;
+        Ok(self.component(type_name).is_ok())
+    }
+
+    /// The index of this transform in its hierarchy, and the hierarchy's local transform and parent
+    /// index arrays.
+    fn hierarchy(&self) -> Result<(i32, Address, Address), Box<dyn Error>> {
+        let transform = self.transform()?.address();
+        let offsets = &self.transform_offsets;
+
+        let read_error = |what: &str| SimpleError::from(&format!("couldnt read {what}"));
+
+        let hierarchy = self
+            .process
+            .read::<u64>(transform + offsets.hierarchy)
+            .map_err(|_| read_error("transform hierarchy"))?;
+        let index = self
+            .process
+            .read::<i32>(transform + offsets.index)
+            .map_err(|_| read_error("transform index"))?;
+        let local_transforms = self
+            .process
+            .read::<u64>(hierarchy + offsets.local_transforms)
+            .map_err(|_| read_error("local transforms"))?;
+        let parent_indices = self
+            .process
+            .read::<u64>(hierarchy + offsets.parent_indices)
+            .map_err(|_| read_error("parent indices"))?;
+
+        Ok((index, local_transforms.into(), parent_indices.into()))
+    }
+
+    fn local_transform(&self) -> Result<TransformX, Box<dyn Error>> {
+        let (index, local_transforms, _) = self.hierarchy()?;
+        TransformX::read(self.process, local_transforms + index as u64 * 0x30)
+    }
+
+    pub fn local_position(&self) -> Result<Vector3, Box<dyn Error>> {
+        Ok(self.local_transform()?.position)
+    }
+
+    pub fn local_scale(&self) -> Result<Vector3, Box<dyn Error>> {
+        Ok(self.local_transform()?.scale)
+    }
+
+    /// The position in world space, by applying every parent's transform to the local position.
+    pub fn world_position(&self) -> Result<Vector3, Box<dyn Error>> {
+        let (mut index, local_transforms, parent_indices) = self.hierarchy()?;
+
+        let mut position = Vector3::default();
+        for _ in 0..MAX_HIERARCHY_DEPTH {
+            let local = TransformX::read(self.process, local_transforms + index as u64 * 0x30)?;
+            position = local.apply(position);
+
+            index = self
+                .process
+                .read::<i32>(parent_indices + index as u64 * 4)
+                .map_err(|_| SimpleError::from("couldnt read parent index"))?;
+            if index < 0 {
+                return Ok(position);
+            }
+        }
+
+        Err(SimpleError::from("transform hierarchy is too deep").into())
+    }
+
+    /// Turn this query into a `Watcher` source, reading whatever `f` asks of the object.
+    pub fn watch<T, F>(self, f: F) -> GameObjectWatch<'a, F>
+    where
+        F: Fn(&GameObjectQuery<'a>) -> Result<T, Box<dyn Error>>,
+    {
+        GameObjectWatch { query: self, f }
+    }
+}
+
+pub struct GameObjectWatch<'a, F> {
+    query: GameObjectQuery<'a>,
+    f: F,
+}
+
+impl<'a, T, F> ValueGetter<T> for GameObjectWatch<'a, F>
+where
+    F: Fn(&GameObjectQuery<'a>) -> Result<T, Box<dyn Error>>,
+{
+    fn get(&self) -> Result<T, Box<dyn Error>> {
+        (self.f)(&self.query)
+    }
+}
+
+impl<'a, T, F> From<GameObjectWatch<'a, F>> for Watcher<'a, T>
+where
+    T: Clone,
+    F: Fn(&GameObjectQuery<'a>) -> Result<T, Box<dyn Error>> + 'a,
+{
+    fn from(value: GameObjectWatch<'a, F>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
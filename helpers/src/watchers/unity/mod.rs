@@ -0,0 +1,490 @@
+use crate::debug;
+use crate::error::SimpleError;
+use crate::watchers::unity::game_object::GameObjectQuery;
+use crate::watchers::unity::scenes::SceneTarget;
+use crate::watchers::{ValueGetter, Watcher};
+use asr::game_engine::unity::scene_manager::{Scene, SceneManager};
+use asr::game_engine::unity::{il2cpp, mono};
+use asr::{Address, PointerSize, Process};
+use bytemuck::CheckedBitPattern;
+use std::cell::{Cell, RefCell};
+use std::error::Error;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+pub mod collections;
+pub mod dump;
+pub mod game_object;
+pub mod scenes;
+
+/// How far up the class hierarchy we look for a field before giving up.
+const MAX_CLASS_DEPTH: usize = 32;
+
+// mono and il2cpp classes have the same API, but no shared trait, hence the macro
+macro_rules! find_field_offset {
+    ($backend:ident, $process:expr, $module:expr, $image:expr, $object:expr, $field:expr) => {{
+        let mut class = $backend::Class::from_object($process, $module, $object).ok();
+        let mut offset = None;
+
+        for _ in 0..MAX_CLASS_DEPTH {
+            let Some(current) = class else {
+                break;
+            };
+
+            offset = current
+                .get_field_offset($process, $module, $field)
+                .or_else(|| {
+                    // the fields of a generic instance live on its generic type definition
+                    let name = current.get_name::<128>($process, $module).ok()?;
+                    let name = name.validate_utf8().ok()?;
+                    if !name.contains('`') {
+                        return None;
+                    }
+
+                    $image
+                        .get_class($process, $module, name)?
+                        .get_field_offset($process, $module, $field)
+                });
+
+            if offset.is_some() {
+                break;
+            }
+
+            class = current.get_parent($process, $module);
+        }
+
+        offset
+    }};
+}
+
+/// The scripting backend the game was built with. Everything in this module works with either, so
+/// game crates usually don't need to care which one they got.
+#[cfg(feature = "unity")]
+#[derive(Clone)]
+pub enum UnityBackend {
+    Mono {
+        module: Rc<mono::Module>,
+        image: mono::Image,
+    },
+    Il2Cpp {
+        module: Rc<il2cpp::Module>,
+        image: il2cpp::Image,
+    },
+}
+
+#[cfg(feature = "unity")]
+#[derive(Clone)]
+pub struct UnityImage<'a> {
+    pub process: &'a Process,
+    pub backend: UnityBackend,
+}
+
+impl<'a> UnityImage<'a> {
+    pub fn new(process: &'a Process, module: Rc<mono::Module>, image: mono::Image) -> Self {
+        UnityImage {
+            process,
+            backend: UnityBackend::Mono { module, image },
+        }
+    }
+
+    pub fn new_il2cpp(
+        process: &'a Process,
+        module: Rc<il2cpp::Module>,
+        image: il2cpp::Image,
+    ) -> Self {
+        UnityImage {
+            process,
+            backend: UnityBackend::Il2Cpp { module, image },
+        }
+    }
+
+    /// Attach to whichever backend the game uses, and load its default image (Assembly-CSharp).
+    ///
+    /// il2cpp is tried first, since a mono game has no `GameAssembly` module, but an il2cpp game
+    /// may still have a mono module lying around.
+    pub fn attach_auto_detect(process: &'a Process) -> Result<Self, Box<dyn Error>> {
+        if let Some(module) = il2cpp::Module::attach_auto_detect(process) {
+            debug!(
+                "  => detected il2cpp {:?}, {:?}",
+                module.get_version(),
+                module.get_pointer_size()
+            );
+            let image = module
+                .get_default_image(process)
+                .ok_or(SimpleError::from("default il2cpp image not found"))?;
+
+            return Ok(Self::new_il2cpp(process, Rc::new(module), image));
+        }
+
+        if let Some(module) = mono::Module::attach_auto_detect(process) {
+            debug!(
+                "  => detected mono {:?}, {:?}",
+                module.get_version(),
+                module.get_pointer_size()
+            );
+            let image = module
+                .get_default_image(process)
+                .ok_or(SimpleError::from("default mono image not found"))?;
+
+            return Ok(Self::new(process, Rc::new(module), image));
+        }
+
+        Err(SimpleError::from("neither a mono nor an il2cpp module was found").into())
+    }
+
+    pub fn pointer_size(&self) -> PointerSize {
+        match &self.backend {
+            UnityBackend::Mono { module, .. } => module.get_pointer_size(),
+            UnityBackend::Il2Cpp { module, .. } => module.get_pointer_size(),
+        }
+    }
+
+    pub fn path(
+        &self,
+        class_name: &'static str,
+        nr_of_parents: usize,
+        fields: &[&'static str],
+    ) -> UnityPointerPath<'a> {
+        let pointer = match &self.backend {
+            UnityBackend::Mono { module, image } => UnityPointerBackend::Mono {
+                module: module.clone(),
+                image: *image,
+                pointer: mono::UnityPointer::new(class_name, nr_of_parents, fields),
+            },
+            UnityBackend::Il2Cpp { module, image } => UnityPointerBackend::Il2Cpp {
+                module: module.clone(),
+                image: *image,
+                pointer: il2cpp::UnityPointer::new(class_name, nr_of_parents, fields),
+            },
+        };
+
+        UnityPointerPath {
+            process: self.process,
+            unity: self.clone(),
+            pointer,
+        }
+    }
+
+    /// Look up the offset of a field on the class of the object at `object`, e.g. a component.
+    ///
+    /// Fields declared on parent classes are found too, as are fields of generic classes (e.g. a
+    /// `Singleton<T>` base class).
+    pub(crate) fn field_offset_of_object(
+        &self,
+        object: Address,
+        field: &str,
+    ) -> Result<u32, Box<dyn Error>> {
+        let offset = match &self.backend {
+            UnityBackend::Mono { module, image } => {
+                find_field_offset!(mono, self.process, module, image, object, field)
+            }
+            UnityBackend::Il2Cpp { module, image } => {
+                find_field_offset!(il2cpp, self.process, module, image, object, field)
+            }
+        };
+
+        offset.ok_or(SimpleError::from(&format!("couldnt get field {field} from class")).into())
+    }
+}
+
+enum UnityPointerBackend {
+    Mono {
+        module: Rc<mono::Module>,
+        image: mono::Image,
+        pointer: mono::UnityPointer<128>,
+    },
+    Il2Cpp {
+        module: Rc<il2cpp::Module>,
+        image: il2cpp::Image,
+        pointer: il2cpp::UnityPointer<128>,
+    },
+}
+
+#[cfg(feature = "unity")]
+pub struct UnityPointerPath<'a> {
+    process: &'a Process,
+    unity: UnityImage<'a>,
+    pointer: UnityPointerBackend,
+}
+
+impl<'a> UnityPointerPath<'a> {
+    /// Read the managed object the path points at, rather than a value in it.
+    fn read_object(&self) -> Result<Address, Box<dyn Error>> {
+        let object: Address = match self.unity.pointer_size() {
+            PointerSize::Bit64 => ValueGetter::<u64>::get(self)?.into(),
+            _ => ValueGetter::<u32>::get(self)?.into(),
+        };
+
+        if object.is_null() {
+            return Err(SimpleError::from("unity pointer points at null").into());
+        }
+
+        Ok(object)
+    }
+}
+
+impl<'a, T: CheckedBitPattern> ValueGetter<T> for UnityPointerPath<'a> {
+    fn get(&self) -> Result<T, Box<dyn Error>> {
+        let value = match &self.pointer {
+            UnityPointerBackend::Mono {
+                module,
+                image,
+                pointer,
+            } => pointer.deref(self.process, module, image),
+            UnityPointerBackend::Il2Cpp {
+                module,
+                image,
+                pointer,
+            } => pointer.deref(self.process, module, image),
+        };
+
+        value.map_err(|_| SimpleError::from("unable to read unity pointer").into())
+    }
+}
+
+impl<'a, T: CheckedBitPattern> From<UnityPointerPath<'a>> for Watcher<'a, T> {
+    fn from(value: UnityPointerPath<'a>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
+
+/// Whether the game object at a path is active. See `GameObjectQuery` for asking anything else.
+pub struct GameObjectActivePath<'a> {
+    query: GameObjectQuery<'a>,
+}
+
+impl<'a> GameObjectActivePath<'a> {
+    pub fn new(
+        process: &'a Process,
+        scene_manager: Rc<SceneManager>,
+        scene: impl Into<SceneTarget>,
+        root_object_name: &'static str,
+        path: &'static [&'static str],
+    ) -> Self {
+        GameObjectActivePath {
+            query: GameObjectQuery::new(
+                process,
+                scene_manager,
+                scene,
+                root_object_name,
+                path.iter().copied(),
+            ),
+        }
+    }
+}
+
+impl<'a> ValueGetter<bool> for GameObjectActivePath<'a> {
+    fn get(&self) -> Result<bool, Box<dyn Error>> {
+        self.query.is_active()
+    }
+}
+
+impl<'a> From<GameObjectActivePath<'a>> for Watcher<'a, bool> {
+    fn from(value: GameObjectActivePath<'a>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
+
+/// Reads a field of a MonoBehaviour (i.e. a component on a game object), following a path of
+/// fields from there.
+///
+/// The component is found by name in the given game object, and cached until its scene is
+/// reloaded. Field offsets are looked up from the runtime class of each object along the path
+/// the first time they're needed.
+///
+/// ```ignore
+/// let watcher: Watcher<bool> = MonoBehaviourFieldPathBuilder::new(&unity, scene_manager)
+///     .scene("scene_win")
+///     .game_object("WinScreen", ["UI", "Canvas", "Scoring", "DifficultyTicker"])
+///     .component("WinScreenTicker")
+///     .field("startedCounting")
+///     .build()?
+///     .into();
+/// ```
+pub struct MonoBehaviourFieldPath<'a, T: CheckedBitPattern> {
+    _phantom: PhantomData<T>,
+    process: &'a Process,
+    unity: UnityImage<'a>,
+    scene_manager: Rc<SceneManager>,
+
+    scene: SceneTarget,
+    root_object_name: String,
+    game_object_path: Vec<String>,
+    component_type_name: String,
+    field_path: Vec<String>,
+
+    // resolved in order, so this can be shorter than field_path
+    offsets: RefCell<Vec<u64>>,
+
+    // (scene address, component address)
+    cached_component: Cell<Option<(Address, Address)>>,
+}
+
+pub struct MonoBehaviourFieldPathBuilder<'a> {
+    unity: UnityImage<'a>,
+    scene_manager: Rc<SceneManager>,
+
+    scene: Option<SceneTarget>,
+    root_object_name: Option<String>,
+    game_object_path: Vec<String>,
+    component_type_name: Option<String>,
+    field_path: Vec<String>,
+}
+
+impl<'a> MonoBehaviourFieldPathBuilder<'a> {
+    pub fn new(unity: &UnityImage<'a>, scene_manager: Rc<SceneManager>) -> Self {
+        MonoBehaviourFieldPathBuilder {
+            unity: unity.clone(),
+            scene_manager,
+            scene: None,
+            root_object_name: None,
+            game_object_path: Vec::new(),
+            component_type_name: None,
+            field_path: Vec::new(),
+        }
+    }
+
+    pub fn scene(mut self, scene: impl Into<SceneTarget>) -> Self {
+        self.scene = Some(scene.into());
+        self
+    }
+
+    /// The root object in the scene, and the path of children's names from there.
+    pub fn game_object<S: Into<String>>(
+        mut self,
+        root_object_name: impl Into<String>,
+        path: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.root_object_name = Some(root_object_name.into());
+        self.game_object_path = path.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The type name of the component on the game object.
+    pub fn component(mut self, component_type_name: impl Into<String>) -> Self {
+        self.component_type_name = Some(component_type_name.into());
+        self
+    }
+
+    /// Add a field to the path. Every field but the last is dereferenced.
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.field_path.push(field.into());
+        self
+    }
+
+    pub fn fields<S: Into<String>>(mut self, fields: impl IntoIterator<Item = S>) -> Self {
+        self.field_path.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build<T: CheckedBitPattern>(
+        self,
+    ) -> Result<MonoBehaviourFieldPath<'a, T>, Box<dyn Error>> {
+        if self.field_path.is_empty() {
+            return Err(
+                SimpleError::from("a MonoBehaviourFieldPath needs at least one field").into(),
+            );
+        }
+
+        Ok(MonoBehaviourFieldPath {
+            _phantom: PhantomData,
+            process: self.unity.process,
+            unity: self.unity,
+            scene_manager: self.scene_manager,
+            scene: self
+                .scene
+                .ok_or(SimpleError::from("a MonoBehaviourFieldPath needs a scene"))?,
+            root_object_name: self.root_object_name.ok_or(SimpleError::from(
+                "a MonoBehaviourFieldPath needs a game object",
+            ))?,
+            game_object_path: self.game_object_path,
+            component_type_name: self.component_type_name.ok_or(SimpleError::from(
+                "a MonoBehaviourFieldPath needs a component",
+            ))?,
+            field_path: self.field_path,
+            offsets: RefCell::new(Vec::new()),
+            cached_component: Cell::new(None),
+        })
+    }
+}
+
+impl<'a, T: CheckedBitPattern> MonoBehaviourFieldPath<'a, T> {
+    fn find_component(&self, scene: &Scene) -> Result<Address, Box<dyn Error>> {
+        let game_object_path = self
+            .game_object_path
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        scene
+            .find_transform(
+                self.process,
+                &self.scene_manager,
+                &self.root_object_name,
+                &game_object_path,
+            )
+            .map_err(|_| SimpleError::from("couldnt find transform"))?
+            .get_game_object(self.process, &self.scene_manager)
+            .map_err(|_| SimpleError::from("couldnt get game_object"))?
+            .get_class(self.process, &self.scene_manager, &self.component_type_name)
+            .map_err(|_| SimpleError::from("couldnt find component in game object").into())
+    }
+
+    /// Make sure every offset along the path is known, looking up any that aren't yet.
+    fn resolve_offsets(&self, component: Address) -> Result<(), Box<dyn Error>> {
+        let mut offsets = self.offsets.borrow_mut();
+        if offsets.len() == self.field_path.len() {
+            return Ok(());
+        }
+
+        let mut current_object = component;
+        for (i, field) in self.field_path.iter().enumerate() {
+            if i == offsets.len() {
+                let offset = self.unity.field_offset_of_object(current_object, field)?;
+                offsets.push(offset as u64);
+            }
+
+            if i + 1 < self.field_path.len() {
+                current_object = self
+                    .process
+                    .read_pointer(current_object + offsets[i], self.unity.pointer_size())
+                    .map_err(|_| SimpleError::from(&format!("couldnt dereference {field}")))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T: CheckedBitPattern> ValueGetter<T> for MonoBehaviourFieldPath<'a, T> {
+    fn get(&self) -> Result<T, Box<dyn Error>> {
+        let scene = self
+            .scene
+            .resolve(self.process, &self.scene_manager)
+            .inspect_err(|_| self.cached_component.set(None))?;
+
+        let component = match self.cached_component.get() {
+            Some((scene_address, component)) if scene_address == scene.address() => component,
+            _ => self.find_component(&scene)?,
+        };
+        self.cached_component
+            .set(Some((scene.address(), component)));
+
+        self.resolve_offsets(component)?;
+
+        let offsets = self.offsets.borrow();
+        self.process
+            .read_pointer_path::<T>(component, self.unity.pointer_size(), &offsets)
+            .map_err(|_| {
+                // the component may have been destroyed, so look for it again next time
+                self.cached_component.set(None);
+                SimpleError::from(&format!("couldnt read {component} {:X?}", *offsets)).into()
+            })
+    }
+}
+
+impl<'a, T: CheckedBitPattern + 'a> From<MonoBehaviourFieldPath<'a, T>> for Watcher<'a, T> {
+    fn from(value: MonoBehaviourFieldPath<'a, T>) -> Self {
+        Watcher::new(Box::new(value))
+    }
+}
@@ -0,0 +1,168 @@
+use crate::debug;
+use crate::error::SimpleError;
+use asr::game_engine::unity::scene_manager::{Scene, SceneManager};
+use asr::Process;
+use std::error::Error;
+use std::rc::Rc;
+
+/// Which scene a game object path should look in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SceneTarget {
+    /// The active scene, but only if it has this name.
+    Active(String),
+    /// Any loaded scene with this name, active or not (e.g. additively loaded scenes).
+    Loaded(String),
+    /// The DontDestroyOnLoad scene, where objects that survive scene changes live.
+    DontDestroyOnLoad,
+}
+
+impl From<&str> for SceneTarget {
+    fn from(name: &str) -> Self {
+        SceneTarget::Active(name.to_owned())
+    }
+}
+
+impl From<String> for SceneTarget {
+    fn from(name: String) -> Self {
+        SceneTarget::Active(name)
+    }
+}
+
+impl SceneTarget {
+    pub fn resolve(
+        &self,
+        process: &Process,
+        scene_manager: &SceneManager,
+    ) -> Result<Scene, Box<dyn Error>> {
+        match self {
+            SceneTarget::Active(name) => {
+                let active_scene = scene_manager
+                    .get_current_scene(process)
+                    .map_err(|_| SimpleError::from("failed to get current scene"))?;
+
+                let active_scene_name = active_scene
+                    .name(process, scene_manager)
+                    .map_err(|_| SimpleError::from("failed getting active scene name"))?;
+
+                if *name != active_scene_name {
+                    return Err(SimpleError::from(&format!(
+                        "in scene {active_scene_name} while expected scene was {name}"
+                    ))
+                    .into());
+                }
+
+                Ok(active_scene)
+            }
+            SceneTarget::Loaded(name) => scene_manager
+                .scenes(process)
+                .find(|scene| scene.name(process, scene_manager).is_ok_and(|n| n == *name))
+                .ok_or(SimpleError::from(&format!("scene {name} is not loaded")).into()),
+            SceneTarget::DontDestroyOnLoad => Ok(scene_manager.get_dont_destroy_on_load_scene()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SceneEvent {
+    /// The active scene changed. `old` is `None` the first time we see a scene.
+    ActiveChanged { old: Option<String>, new: String },
+    /// A scene was loaded, either as the new active scene or additively.
+    Loaded(String),
+    /// A scene that was loaded isn't anymore.
+    Unloaded(String),
+}
+
+/// Keeps track of the active scene and every loaded scene, and what changed since the last update.
+///
+/// Call `update` once per tick (before reading anything that depends on scenes), then check
+/// `events` for what happened.
+pub struct Scenes<'a> {
+    process: &'a Process,
+    scene_manager: Rc<SceneManager>,
+
+    active: Option<String>,
+    loaded: Vec<String>,
+    events: Vec<SceneEvent>,
+}
+
+impl<'a> Scenes<'a> {
+    pub fn new(process: &'a Process, scene_manager: Rc<SceneManager>) -> Self {
+        Scenes {
+            process,
+            scene_manager,
+            active: None,
+            loaded: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.events.clear();
+
+        let active = self
+            .scene_manager
+            .get_current_scene(self.process)
+            .and_then(|scene| scene.name(self.process, &self.scene_manager))
+            .map_err(|_| SimpleError::from("failed getting active scene name"))?;
+
+        let loaded = self
+            .scene_manager
+            .scenes(self.process)
+            .filter_map(|scene| scene.name(self.process, &self.scene_manager).ok())
+            .collect::<Vec<_>>();
+
+        // keep the scenes we already knew about in the order they were loaded, and add new ones
+        // at the end, so `loaded` stays in load order
+        let mut still_loaded = Vec::with_capacity(loaded.len());
+        for name in self.loaded.drain(..) {
+            if loaded.contains(&name) {
+                still_loaded.push(name);
+            } else {
+                self.events.push(SceneEvent::Unloaded(name));
+            }
+        }
+        for name in loaded {
+            if !still_loaded.contains(&name) {
+                self.events.push(SceneEvent::Loaded(name.clone()));
+                still_loaded.push(name);
+            }
+        }
+        self.loaded = still_loaded;
+
+        if self.active.as_ref() != Some(&active) {
+            debug!("active scene changed from {:?} to {active}", self.active);
+            self.events.push(SceneEvent::ActiveChanged {
+                old: self.active.replace(active.clone()),
+                new: active,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Every loaded scene, in the order they were loaded.
+    pub fn loaded(&self) -> &[String] {
+        &self.loaded
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.loaded.iter().any(|scene| scene == name)
+    }
+
+    /// What changed in the last `update`.
+    pub fn events(&self) -> &[SceneEvent] {
+        &self.events
+    }
+
+    /// The old and new name, if the active scene changed in the last `update`.
+    pub fn active_changed(&self) -> Option<(Option<&str>, &str)> {
+        self.events.iter().find_map(|event| match event {
+            SceneEvent::ActiveChanged { old, new } => Some((old.as_deref(), new.as_str())),
+            _ => None,
+        })
+    }
+}
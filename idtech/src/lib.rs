@@ -6,6 +6,7 @@ use std::rc::Rc;
 use crate::typeinfo::class::ClassTypeInfo;
 use asr::{signature::Signature, Address, Process};
 use helpers::error::SimpleError;
+use helpers::{debug, info};
 use typeinfo::*;
 
 pub struct IdTech<'a> {
@@ -20,16 +21,14 @@ impl<'a> IdTech<'a> {
         version: IdTechVersion,
         main_module_name: &str,
     ) -> Result<IdTech<'a>, Box<dyn Error>> {
-        asr::print_message(&format!("  => idtech: Using version {version:?}"));
+        info!("  => idtech: Using version {version:?}");
 
         let memory = Rc::new(Memory::new(process, version, main_module_name)?);
         let typeinfo_instance = process
             .read::<u64>(memory.typeinfo_addr)
             .map_err(|_| SimpleError::from("failed to read typeinfo_addr ()"))?
             .into();
-        asr::print_message(&format!(
-            "  => found typeinfo instance at 0x{typeinfo_instance:?}"
-        ));
+        debug!("  => found typeinfo instance at 0x{typeinfo_instance:?}");
 
         if typeinfo_instance == Address::NULL {
             return Err(SimpleError::from("idtech: the typeinfo instance is null").into());
@@ -100,9 +99,7 @@ fn find_addr_or_panic(
 ) -> Address {
     for (i, sig) in sigs.iter().enumerate() {
         if let Ok(addr) = sig(process, module_range) {
-            asr::print_message(&format!(
-                "  => Found {name} at 0x{addr} with signature index {i}"
-            ));
+            debug!("  => Found {name} at 0x{addr} with signature index {i}");
             return addr;
         }
     }
@@ -3,6 +3,7 @@
 use crate::typeinfo::class::ClassTypeInfo;
 use asr::string::ArrayCString;
 use asr::{Address, Process};
+use helpers::debug;
 use helpers::error::SimpleError;
 use std::collections::HashMap;
 use std::error::Error;
@@ -86,7 +87,7 @@ impl<'a> TypeInfoProject<'a> {
             .map_err(|_| SimpleError::from("failed to read name of project"))?
             .validate_utf8()?
             .to_owned();
-        asr::print_message(&format!("  => found project {name} at address {address}"));
+        debug!("  => found project {name} at address {address}");
 
         let mut classes = HashMap::new();
         let base_class_addr: Address = process
@@ -111,7 +112,7 @@ impl<'a> TypeInfoProject<'a> {
             )
             .map_err(|_| SimpleError::from("failed to get number of classes"))?
             as u64;
-        asr::print_message(&format!("    => found {num_classes} classes"));
+        debug!("    => found {num_classes} classes");
         for class_index in 0u64..num_classes {
             let class_addr = base_class_addr + class_index * CLASS_TYPE_INFO_SIZE;
             let class_val = process
@@ -124,7 +125,7 @@ impl<'a> TypeInfoProject<'a> {
 
             classes.insert(class.name.clone(), class);
         }
-        asr::print_message("    => finished preloading those classes");
+        debug!("    => finished preloading those classes");
 
         Ok(TypeInfoProject {
             _process: process,
@@ -11,6 +11,7 @@ use asr::timer::{
 use bitflags::bitflags;
 use bytemuck::{CheckedBitPattern, Pod, Zeroable};
 use helpers::settings::initialise_settings;
+use helpers::watchers::gba::{RomHeader, REFRESH_RATE};
 use helpers::watchers::Watcher;
 use helpers::{better_split, get_setting};
 use std::collections::{HashMap, HashSet};
@@ -19,6 +20,9 @@ use std::fmt::Debug;
 
 asr::async_main!(stable);
 
+/// Every release's game code starts with this, the last letter is the region.
+const GAME_CODE_PREFIX: &str = "AKR";
+
 async fn main() {
     std::panic::set_hook(Box::new(|panic_info| {
         asr::print_message(&panic_info.to_string());
@@ -86,7 +90,7 @@ enum GameMode {
 }
 
 fn get_in_game_time(frames: u32) -> Duration {
-    Duration::seconds_f32((frames as f32) / 60_f32)
+    REFRESH_RATE.duration(frames as u64)
 }
 
 fn flag_just_enabled<'a>(
@@ -116,6 +120,27 @@ async fn on_attach(
     //     &format!("0x{}", emulator.ram_base.get().unwrap().get(1).unwrap()),
     // );
 
+    // wait for kuru to be loaded, since the emulator may have the wrong game (or none) loaded
+    let mut last_error = None;
+    let header = loop {
+        let error = match RomHeader::read(emulator) {
+            Ok(header) if header.game_code.starts_with(GAME_CODE_PREFIX) => break header,
+            Ok(header) => format!("not kuru kuru kururin: {header}"),
+            Err(e) => e.to_string(),
+        };
+
+        if last_error.as_ref() != Some(&error) {
+            asr::print_message(&format!("Not running: {error}"));
+            set_variable("game", &error);
+            last_error = Some(error);
+        }
+
+        next_tick().await;
+    };
+
+    asr::print_message(&format!("Detected {header}"));
+    set_variable("game", &header.to_string());
+
     let mut watchers = Watchers::init(emulator);
     let mut completed_splits = HashSet::new();
 
@@ -2,19 +2,22 @@
 extern crate helpers;
 use asr::settings::gui::Title;
 use asr::settings::Gui;
-use asr::string::ArrayCString;
 use asr::time::Duration;
-use asr::{future::next_tick, timer, watcher::Watcher, Address, Error, Process};
+use asr::{timer, watcher::Watcher, Address, Error, Process};
+use helpers::error::SimpleError;
+use helpers::fingerprint::{self, Fingerprint, KnownBuild};
+use helpers::runtime::AutoSplitter;
+use helpers::variables::{self, Group};
 use std::collections::{HashMap, HashSet};
+use std::error::Error as StdError;
 use std::fmt::Debug;
-use zdoom::pclass::PClass;
 use zdoom::tarray::TArray;
 use zdoom::{
     player::{DVector3, PlayerState},
     GameAction, ZDoom, ZDoomVersion,
 };
 
-use helpers::{impl_auto_splitter_state, split};
+use helpers::{debug, impl_auto_splitter_state, split};
 
 asr::async_main!(stable);
 
@@ -145,28 +148,7 @@ struct Settings {
 }
 
 async fn main() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        asr::print_message(&panic_info.to_string());
-    }));
-
-    asr::print_message("Hello, World!");
-
-    let mut settings = Settings::register();
-
-    loop {
-        let process = Process::wait_attach("SELACO.exe").await;
-        process
-            .until_closes(async {
-                on_attach(&process, &mut settings).await.expect("problem");
-            })
-            .await;
-    }
-}
-
-struct FoundClasses<'a> {
-    objectives_class: PClass<'a>,
-    objective_class: PClass<'a>,
-    _actor_class: PClass<'a>,
+    helpers::runtime::run::<Selaco>().await;
 }
 
 #[derive(PartialEq)]
@@ -178,61 +160,85 @@ enum LevelTransitionLoadState {
     ActionAutoSaveAfterWorldDone,
 }
 
-async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Option<Error>> {
-    let (mut zdoom, classes) = ZDoom::wait_try_load(
-        process,
-        ZDoomVersion::Gzdoom4_8Pre,
-        "Selaco.exe",
-        |classes| {
-            let objectives_class = classes.get("Objectives").ok_or(None)?.to_owned();
-            let objective_class = classes.get("Objective").ok_or(None)?.to_owned();
-            let actor_class = classes.get("Actor").ok_or(None)?.to_owned();
-
-            Ok(FoundClasses {
-                objectives_class,
-                objective_class,
-                _actor_class: actor_class,
-            })
-        },
-    )
-    .await;
-    // let _ = zdoom.dump();
-    // let _ = zdoom.level.dump_actors(&classes.actor_class);
-
-    let mut watchers = Watchers::default();
-    let mut completed_splits = HashSet::new();
-    let mut level_transition_state = LevelTransitionLoadState::NotTransitioning;
-
-    loop {
-        if !process.is_open() {
-            asr::print_message("process not open");
-            return Ok(());
-        }
+/// The engine version the splitter was written against, used for builds we haven't checked.
+const DEFAULT_VERSION: ZDoomVersion = ZDoomVersion::Gzdoom4_8Pre;
 
-        settings.update();
+/// Selaco builds we've checked, and the engine version each is on. Other builds are shown as
+/// unknown (with their fingerprint, so they can be reported) and use `DEFAULT_VERSION`.
+const KNOWN_BUILDS: &[KnownBuild<ZDoomVersion>] = &[];
 
-        let res = watchers.update(process, &mut zdoom, &classes);
-        if res.is_err() {
-            asr::print_message("failed updating watchers");
-            continue;
-        }
+struct Selaco<'a> {
+    process: &'a Process,
+    zdoom: ZDoom<'a>,
+    watchers: Watchers,
+    completed_splits: HashSet<String>,
+    level_transition_state: LevelTransitionLoadState,
+}
 
-        let states = watchers.to_states();
-        if states.is_none() {
-            asr::print_message("some watcher is empty");
-            continue;
-        }
+impl AutoSplitter for Selaco<'_> {
+    const PROCESS_NAMES: &'static [&'static str] = &["SELACO.exe"];
 
-        let (old, current) = states.unwrap();
+    type Settings = Settings;
+    type State<'a> = Selaco<'a>;
 
-        match level_transition_state {
+    async fn load<'a>(
+        process: &'a Process,
+        _process_name: &'static str,
+    ) -> Result<Selaco<'a>, Box<dyn StdError>> {
+        let version = Fingerprint::read(process, "Selaco.exe")
+            .ok()
+            .and_then(|fingerprint| fingerprint::identify(&fingerprint, KNOWN_BUILDS))
+            .map_or(DEFAULT_VERSION, |build| build.value);
+
+        // the objectives are read through these, so wait until they're all loaded
+        let (zdoom, _) = ZDoom::wait_try_load(process, Some(version), "Selaco.exe", |classes| {
+            for name in ["Objectives", "Objective", "Actor"] {
+                classes.get(name).ok_or(None)?;
+            }
+
+            Ok(())
+        })
+        .await;
+        // let _ = zdoom.dump();
+        // let _ = zdoom.level.dump_actors(zdoom.find_class("Actor")?.unwrap());
+
+        Ok(Selaco {
+            process,
+            zdoom,
+            watchers: Watchers::default(),
+            completed_splits: HashSet::new(),
+            level_transition_state: LevelTransitionLoadState::NotTransitioning,
+        })
+    }
+
+    async fn tick(
+        selaco: &mut Self::State<'_>,
+        settings: &Settings,
+    ) -> Result<(), Box<dyn StdError>> {
+        let Selaco {
+            process,
+            zdoom,
+            watchers,
+            completed_splits,
+            level_transition_state,
+        } = selaco;
+
+        watchers
+            .update(process, zdoom)
+            .map_err(|_| SimpleError::from("failed updating watchers"))?;
+
+        let (old, current) = watchers
+            .to_states()
+            .ok_or(SimpleError::from("some watcher is empty"))?;
+
+        match *level_transition_state {
             LevelTransitionLoadState::NotTransitioning => {
                 match current.gameaction {
                     GameAction::Completed => {
-                        level_transition_state = LevelTransitionLoadState::ActionCompleted
+                        *level_transition_state = LevelTransitionLoadState::ActionCompleted
                     }
                     GameAction::WorldDone => {
-                        level_transition_state = LevelTransitionLoadState::ActionWorldDone
+                        *level_transition_state = LevelTransitionLoadState::ActionWorldDone
                     }
                     _ => {}
                 };
@@ -240,44 +246,44 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
             LevelTransitionLoadState::ActionCompleted => {
                 match current.gameaction {
                     GameAction::Nothing => {
-                        level_transition_state =
+                        *level_transition_state =
                             LevelTransitionLoadState::AfterCompletedBeforeWorldDone
                     }
                     GameAction::WorldDone => {
-                        level_transition_state = LevelTransitionLoadState::ActionWorldDone
+                        *level_transition_state = LevelTransitionLoadState::ActionWorldDone
                     }
                     _ => {}
                 };
             }
             LevelTransitionLoadState::AfterCompletedBeforeWorldDone => {
                 if current.gameaction == GameAction::WorldDone {
-                    level_transition_state = LevelTransitionLoadState::ActionWorldDone;
+                    *level_transition_state = LevelTransitionLoadState::ActionWorldDone;
                 }
             }
             LevelTransitionLoadState::ActionWorldDone => match current.gameaction {
                 GameAction::AutoSave => {
-                    level_transition_state = LevelTransitionLoadState::ActionAutoSaveAfterWorldDone
+                    *level_transition_state = LevelTransitionLoadState::ActionAutoSaveAfterWorldDone
                 }
                 GameAction::Nothing => {
-                    level_transition_state = LevelTransitionLoadState::NotTransitioning
+                    *level_transition_state = LevelTransitionLoadState::NotTransitioning
                 }
                 _ => {}
             },
             LevelTransitionLoadState::ActionAutoSaveAfterWorldDone => {
                 if current.gameaction == GameAction::Nothing {
-                    level_transition_state = LevelTransitionLoadState::NotTransitioning;
+                    *level_transition_state = LevelTransitionLoadState::NotTransitioning;
                 }
             }
         }
 
         if timer::state() == timer::TimerState::NotRunning {
-            completed_splits = HashSet::new();
+            *completed_splits = HashSet::new();
 
             if current.level == "SE_01a"
                 && old.gameaction == GameAction::NewGame
                 && current.gameaction != GameAction::NewGame
             {
-                completed_splits = HashSet::new();
+                *completed_splits = HashSet::new();
                 timer::start();
                 asr::timer::set_game_time(Duration::ZERO);
             }
@@ -285,7 +291,7 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
 
         if timer::state() == timer::TimerState::Running {
             // isLoading
-            if level_transition_state != LevelTransitionLoadState::NotTransitioning
+            if *level_transition_state != LevelTransitionLoadState::NotTransitioning
                 || current.playerstate == PlayerState::Enter
             {
                 timer::pause_game_time();
@@ -301,7 +307,7 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
                     if old_objective_status == 0 && *current_objective_status != 0 {
                         asr::print_message(&format!("completed {objective_key}"));
 
-                        split(&objective_key, &mut completed_splits);
+                        split(&objective_key, completed_splits);
                     }
                 }
             }
@@ -324,7 +330,11 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Opt
         //     }
         // }
 
-        next_tick().await;
+        Ok(())
+    }
+
+    fn invalidate(selaco: &mut Self::State<'_>) {
+        let _ = selaco.zdoom.invalidate_cache();
     }
 }
 
@@ -341,30 +351,47 @@ impl Watchers {
         &mut self,
         process: &'a Process,
         zdoom: &mut ZDoom<'a>,
-        classes: &FoundClasses<'a>,
     ) -> Result<(), Option<Error>> {
-        zdoom.invalidate_cache().expect("");
-
+        // these were always shown before there were groups, so keep them on by default
         let gameaction = zdoom.gameaction().unwrap_or_default();
-        timer::set_variable("gameaction", &format!("{:?}", gameaction));
+        variables::set(Group::RunRecap, "gameaction", || {
+            format!("{:?}", gameaction)
+        });
         self.gameaction.update(Some(gameaction));
 
         let level_name = zdoom.level.name().map(|s| s.to_owned()).unwrap_or_default();
-        timer::set_variable("map", level_name.as_str());
+        variables::set(Group::RunRecap, "map", || level_name.to_owned());
         self.level.update(Some(level_name));
 
         let player = zdoom.player()?;
         let playerstate = player.state()?.to_owned();
-        timer::set_variable("playerstate", &format!("{:?}", playerstate));
+        variables::set(Group::RunRecap, "playerstate", || {
+            format!("{:?}", playerstate)
+        });
         self.playerstate.update(Some(playerstate));
 
         let player_pos = player.pos().map(|v| v.to_owned()).unwrap_or_default();
-        timer::set_variable("pos", &format!("{:?}", player_pos));
+        variables::set(Group::RunRecap, "pos", || format!("{:?}", player_pos));
         self.player_pos.update(Some(player_pos));
 
+        if let Ok(stats) = zdoom.level_stats() {
+            variables::set(Group::RunRecap, "kills", || {
+                format!("{}/{}", stats.kills, stats.total_kills)
+            });
+            variables::set(Group::RunRecap, "items", || {
+                format!("{}/{}", stats.items, stats.total_items)
+            });
+            variables::set(Group::RunRecap, "secrets", || {
+                format!("{}/{}", stats.secrets, stats.total_secrets)
+            });
+            variables::set(Group::RunRecap, "level time", || {
+                format!("{:.2}s", stats.map_time().as_seconds_f64())
+            });
+        }
+
         let (objectives, objective_history) =
-            get_completed_objectives(process, zdoom, classes).unwrap_or_default();
-        timer::set_variable("objectives", &format!("{:#?}", objectives));
+            get_completed_objectives(process, zdoom).unwrap_or_default();
+        variables::set(Group::Debug, "objectives", || format!("{:#?}", objectives));
         // timer::set_variable("history", &format!("{:#?}", objective_history));
 
         let mut map = HashMap::new();
@@ -376,9 +403,24 @@ impl Watchers {
 
         self.objective_status.update(Some(map));
 
-        // let mut actors = zdoom.level.get_actor_names(&classes.actor_class)?;
-        // actors.sort();
-        // timer::set_variable("actors", &format!("{:#?}", actors));
+        variables::set(Group::Debug, "actors", || {
+            zdoom
+                .thinkers_of_class("Actor", None)
+                .map(|actors| actors.count().to_string())
+                .unwrap_or_else(|_| "unknown".to_owned())
+        });
+        variables::set(Group::Debug, "event handlers", || {
+            zdoom
+                .event_handlers()
+                .map(|handlers| {
+                    handlers
+                        .iter()
+                        .filter_map(|handler| handler.class().name().ok().map(|n| n.to_owned()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_else(|_| "unknown".to_owned())
+        });
 
         Ok(())
     }
@@ -400,73 +442,30 @@ struct Objective {
 }
 
 impl Objective {
-    pub fn read(
-        process: &Process,
-        address: Address,
-        classes: &FoundClasses,
-    ) -> Result<Self, Option<Error>> {
-        let children_offset = classes
-            .objective_class
-            .fields()?
-            .get("children")
-            .ok_or(None)?
-            .offset()?
-            .to_owned() as u64;
-        let title_offset = classes
-            .objective_class
-            .fields()?
-            .get("title")
-            .ok_or(None)?
-            .offset()?
-            .to_owned() as u64;
-        let status_offset = classes
-            .objective_class
-            .fields()?
-            .get("status")
-            .ok_or(None)?
-            .offset()?
-            .to_owned() as u64;
-        let tag_offset = classes
-            .objective_class
-            .fields()?
-            .get("tag")
-            .ok_or(None)?
-            .offset()?
-            .to_owned() as u64;
-
-        let title = process
-            .read_pointer_path::<ArrayCString<128>>(
-                address,
-                asr::PointerSize::Bit64,
-                &[title_offset, 0x0],
-            )?
-            .validate_utf8()
-            .expect("title should always be utf-8")
-            .to_owned();
-
-        let tag = process.read(address + tag_offset)?;
-        let status = process.read(address + status_offset)?;
-        let children = read_objectives(process, address + children_offset, classes)?;
+    pub fn read(process: &Process, zdoom: &ZDoom, address: Address) -> Result<Self, Option<Error>> {
+        let objective = zdoom.object(address)?;
 
         Ok(Objective {
-            _title: title,
-            tag,
-            status,
-            children,
+            _title: objective.get_string("title")?,
+            // tags can be negative, which were always read as their bits
+            tag: objective.get_i32("tag")? as u32,
+            status: objective.get_u32("status")?,
+            children: read_objectives(process, zdoom, objective.field_address("children")?)?,
         })
     }
 }
 
 fn read_objectives(
     process: &Process,
+    zdoom: &ZDoom,
     address: Address,
-    classes: &FoundClasses,
 ) -> Result<Vec<Objective>, Option<Error>> {
     let objectives_arr = TArray::new(process, address);
     let mut objectives = Vec::new();
     for objective in objectives_arr.iter::<u64>()? {
-        if let Ok(obj) = Objective::read(process, objective.into(), classes) {
-            objectives.push(obj);
+        match Objective::read(process, zdoom, objective.into()) {
+            Ok(obj) => objectives.push(obj),
+            Err(e) => debug!("Couldn't read the objective at 0x{objective:X}: {e:?}"),
         }
     }
     Ok(objectives)
@@ -475,40 +474,19 @@ fn read_objectives(
 fn get_completed_objectives(
     process: &Process,
     zdoom: &ZDoom,
-    classes: &FoundClasses,
 ) -> Result<(Vec<Objective>, Vec<Objective>), Option<Error>> {
-    let objs_offset = classes
-        .objectives_class
-        .fields()?
-        .get("objs")
-        .ok_or(None)?
-        .offset()?
-        .to_owned() as u64;
-    let history_offset = classes
-        .objectives_class
-        .fields()?
-        .get("history")
-        .ok_or(None)?
-        .offset()?
-        .to_owned() as u64;
-
     let player = zdoom.player()?;
     let player_inventories = player.get_inventories()?;
 
     for inv in player_inventories {
-        let class = process.read::<u64>(inv + 0x8)?.into();
-        let class = PClass::new(
-            process,
-            zdoom.memory.clone(),
-            zdoom.name_data.clone(),
-            class,
-        );
+        let Ok(inventory) = zdoom.object(inv) else {
+            continue;
+        };
 
-        let name = class.name()?;
-        if name == "Objectives" {
+        if inventory.class().name()? == "Objectives" {
             return Ok((
-                read_objectives(process, inv + objs_offset, classes)?,
-                read_objectives(process, inv + history_offset, classes)?,
+                read_objectives(process, zdoom, inventory.field_address("objs")?)?,
+                read_objectives(process, zdoom, inventory.field_address("history")?)?,
             ));
         }
     }
@@ -2,8 +2,11 @@
 extern crate helpers;
 use asr::settings::gui::Title;
 use asr::settings::Gui;
-use asr::{future::next_tick, print_message, timer, watcher::Watcher, Error, Process};
+use asr::{print_message, timer, watcher::Watcher, Error, Process};
+use helpers::error::SimpleError;
+use helpers::runtime::AutoSplitter;
 use std::collections::HashSet;
+use std::error::Error as StdError;
 use zdoom::{player::DVector3, GameAction, ZDoom, ZDoomVersion};
 
 use helpers::{impl_auto_splitter_state, split};
@@ -39,63 +42,74 @@ struct Settings {
 }
 
 async fn main() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        asr::print_message(&panic_info.to_string());
-    }));
-
-    asr::print_message("Hello, World!");
-
-    let mut settings = Settings::register();
-
-    loop {
-        let process = Process::wait_attach("gzdoom.exe").await;
-        process
-            .until_closes(async {
-                on_attach(&process, &mut settings).await.expect("problem");
-            })
-            .await;
-    }
+    helpers::runtime::run::<SnapTheSentinel>().await;
 }
 
-async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Error> {
-    let (mut zdoom, _) =
-        ZDoom::wait_try_load(process, ZDoomVersion::Gzdoom4_8_2, "gzdoom.exe", |_| Ok(())).await;
-    // zdoom.dump();
-    // if let Ok(p) = zdoom.player() {
-    //     p.dump_inventories(&zdoom.name_data);
-    // }
-    if zdoom
-        .level
-        .dump_actors(zdoom.classes().unwrap().get("Actor").unwrap())
-        .is_err()
-    {
-        print_message("there was an error, but good luck knowing what it was");
-    }
-
-    let mut watchers = Watchers::default();
-    let mut completed_splits = HashSet::new();
+struct SnapTheSentinel<'a> {
+    zdoom: ZDoom<'a>,
+    watchers: Watchers,
+    completed_splits: HashSet<String>,
+}
 
-    loop {
-        if !process.is_open() {
-            asr::print_message("process not open");
-            return Ok(());
+impl AutoSplitter for SnapTheSentinel<'_> {
+    const PROCESS_NAMES: &'static [&'static str] = &[
+        // Windows
+        "gzdoom.exe",
+        // Linux
+        "gzdoom",
+    ];
+
+    type Settings = Settings;
+    type State<'a> = SnapTheSentinel<'a>;
+
+    async fn load<'a>(
+        process: &'a Process,
+        process_name: &'static str,
+    ) -> Result<SnapTheSentinel<'a>, Box<dyn StdError>> {
+        let (zdoom, _) = ZDoom::wait_try_load(
+            process,
+            Some(ZDoomVersion::Gzdoom4_8_2),
+            process_name,
+            |_| Ok(()),
+        )
+        .await;
+        // zdoom.dump();
+        // if let Ok(p) = zdoom.player() {
+        //     p.dump_inventories(&zdoom.name_data);
+        // }
+        let actor_class = zdoom
+            .classes()
+            .ok()
+            .and_then(|classes| classes.get("Actor"))
+            .ok_or(SimpleError::from("failed to find the Actor class"))?;
+        if zdoom.level.dump_actors(actor_class).is_err() {
+            print_message("there was an error, but good luck knowing what it was");
         }
 
-        settings.update();
-        let res = watchers.update(process, &mut zdoom);
+        Ok(SnapTheSentinel {
+            zdoom,
+            watchers: Watchers::default(),
+            completed_splits: HashSet::new(),
+        })
+    }
 
-        if res.is_err() {
-            asr::print_message("failed updating watchers");
-            continue;
-        }
+    async fn tick(
+        snap: &mut Self::State<'_>,
+        settings: &Settings,
+    ) -> Result<(), Box<dyn StdError>> {
+        let SnapTheSentinel {
+            zdoom,
+            watchers,
+            completed_splits,
+        } = snap;
 
-        let states = watchers.to_states();
-        if states.is_none() {
-            asr::print_message("some watcher is empty");
-            continue;
-        }
+        watchers
+            .update(zdoom)
+            .map_err(|_| SimpleError::from("failed updating watchers"))?;
 
-        let (old, current) = states.unwrap();
+        let (old, current) = watchers
+            .to_states()
+            .ok_or(SimpleError::from("some watcher is empty"))?;
 
         if timer::state() == timer::TimerState::NotRunning
             && current.level == "E1M1"
@@ -104,7 +118,7 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Err
             && old.gameaction == GameAction::NewGame
             && current.gameaction == GameAction::Nothing
         {
-            completed_splits = HashSet::new();
+            *completed_splits = HashSet::new();
             timer::start();
         }
 
@@ -116,40 +130,36 @@ async fn on_attach(process: &Process, settings: &mut Settings) -> Result<(), Err
 
             if old.level != current.level {
                 let key = format!("_level_{}_{}", old.level, current.level).to_lowercase();
-                split(&key, &mut completed_splits);
+                split(&key, completed_splits);
             }
 
             if let Some(old_health) = old.ocean_health {
                 if let Some(current_health) = current.ocean_health {
                     if settings.ocean_death && old_health > 0 && current_health == 0 {
-                        split(&String::from("ocean_death"), &mut completed_splits);
+                        split(&String::from("ocean_death"), completed_splits);
                     }
                 }
             }
         }
 
-        next_tick().await;
+        Ok(())
+    }
+
+    fn invalidate(snap: &mut Self::State<'_>) {
+        let _ = snap.zdoom.invalidate_cache();
     }
 }
 
-pub fn get_ocean_health(process: &Process, zdoom: &mut ZDoom) -> Option<u32> {
+pub fn get_ocean_health(zdoom: &mut ZDoom) -> Option<u32> {
     let res: Result<u32, Option<Error>> = (|| {
         if zdoom.level.name()? != "E1M10" {
             return Err(None);
         }
 
-        let ocean = zdoom.level.find_actor("DummyOcean")?;
-        let actor_health_offset = zdoom
-            .classes()?
-            .get("Actor")
-            .unwrap()
-            .fields()?
-            .get("Health")
-            .unwrap()
-            .offset()?
-            .to_owned();
-
-        Ok(process.read::<u32>(ocean + actor_health_offset)?)
+        let actor_class = zdoom.find_class("Actor")?.ok_or(None)?;
+        let ocean = zdoom.level.find_actor(actor_class, "DummyOcean")?;
+
+        zdoom.object(ocean)?.get_u32("Health")
     })();
 
     if let Ok(health) = res {
@@ -167,9 +177,7 @@ impl_auto_splitter_state!(Watchers {
 });
 
 impl Watchers {
-    fn update(&mut self, process: &Process, zdoom: &mut ZDoom) -> Result<(), Option<Error>> {
-        zdoom.invalidate_cache().expect("");
-
+    fn update(&mut self, zdoom: &mut ZDoom) -> Result<(), Option<Error>> {
         self.gameaction
             .update(Some(zdoom.gameaction().unwrap_or_default()));
 
@@ -180,8 +188,7 @@ impl Watchers {
         let player_pos = player.pos().map(|v| v.to_owned()).unwrap_or_default();
         self.player_pos.update(Some(player_pos));
 
-        self.ocean_health
-            .update(Some(get_ocean_health(process, zdoom)));
+        self.ocean_health.update(Some(get_ocean_health(zdoom)));
 
         Ok(())
     }
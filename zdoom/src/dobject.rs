@@ -0,0 +1,308 @@
+use std::rc::Rc;
+
+use asr::{Address, Error, Process};
+use helpers::debug;
+
+use crate::fstring::FString;
+use crate::name_manager::{FName, NameManager};
+use crate::pclass::{PClass, PField, PFieldFlags, TypeFlags};
+use crate::player::DVector3;
+use crate::tarray::{TArray, TMap};
+
+/// A field's value, decoded according to its type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int8(i8),
+    UInt8(u8),
+    Int16(i16),
+    UInt16(u16),
+    Int32(i32),
+    UInt32(u32),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Name(String),
+    /// A pointer to another object, which is null if there's no object. Use `ZDoom::object` to
+    /// read it.
+    Object(Address),
+    /// A `TArray`, where the elements are of type `element_type`. Use `DObject::get_array` or
+    /// `TArray::read_values` to read them.
+    Array {
+        address: Address,
+        element_type: String,
+    },
+    /// A `Map<K, V>`. Use `DObject::get_map` or `TMap::read_values` to read the entries.
+    Map {
+        address: Address,
+        key_type: String,
+        value_type: String,
+    },
+    Vector2([f64; 2]),
+    Vector3(DVector3),
+    /// Anything we don't decode (structs, sounds, states, ...), and where it is.
+    Unknown {
+        type_name: String,
+        address: Address,
+    },
+}
+
+impl Value {
+    /// Any integer (or bool), widened.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Bool(v) => Some(v as i64),
+            Value::Int8(v) => Some(v as i64),
+            Value::UInt8(v) => Some(v as i64),
+            Value::Int16(v) => Some(v as i64),
+            Value::UInt16(v) => Some(v as i64),
+            Value::Int32(v) => Some(v as i64),
+            Value::UInt32(v) => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Float(v) => Some(v as f64),
+            Value::Double(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// Read a value of the type named `type_name` (a `PType`'s descriptive name).
+    pub fn read(
+        process: &Process,
+        name_manager: &NameManager,
+        type_name: &str,
+        address: Address,
+    ) -> Result<Value, Option<Error>> {
+        if unwrap_type_name(type_name, "Pointer").is_some() {
+            return Ok(Value::Object(process.read::<u64>(address)?.into()));
+        }
+        if let Some(element_type) = unwrap_type_name(type_name, "DynArray") {
+            return Ok(Value::Array {
+                address,
+                element_type: element_type.to_owned(),
+            });
+        }
+        if let Some((key_type, value_type)) =
+            unwrap_type_name(type_name, "Map").and_then(split_type_args)
+        {
+            return Ok(Value::Map {
+                address,
+                key_type: key_type.to_owned(),
+                value_type: value_type.to_owned(),
+            });
+        }
+
+        let value = match unwrap_type_name(type_name, "Struct").unwrap_or(type_name) {
+            "Bool" => Value::Bool(process.read::<u8>(address)? != 0),
+            "SInt1" => Value::Int8(process.read(address)?),
+            "UInt1" => Value::UInt8(process.read(address)?),
+            "SInt2" => Value::Int16(process.read(address)?),
+            "UInt2" => Value::UInt16(process.read(address)?),
+            "SInt4" => Value::Int32(process.read(address)?),
+            "UInt4" => Value::UInt32(process.read(address)?),
+            "Float4" => Value::Float(process.read(address)?),
+            "Float8" => Value::Double(process.read(address)?),
+            "String" => Value::String(FString::read(process, address)?),
+            "Name" => Value::Name(name_manager.get(FName::read(process, address)?)?),
+            "Vector2" => Value::Vector2(process.read(address)?),
+            "Vector3" => Value::Vector3(DVector3::read(process, address)?),
+            _ => Value::Unknown {
+                type_name: type_name.to_owned(),
+                address,
+            },
+        };
+
+        Ok(value)
+    }
+
+    /// The (size, alignment) of a value of the type named `type_name`, if it's one we know.
+    pub fn layout(type_name: &str) -> Option<(u64, u64)> {
+        let layout = match unwrap_type_name(type_name, "Struct").unwrap_or(type_name) {
+            "Bool" | "SInt1" | "UInt1" => (0x1, 0x1),
+            "SInt2" | "UInt2" => (0x2, 0x2),
+            "SInt4" | "UInt4" | "Float4" | "Name" => (0x4, 0x4),
+            "Float8" | "String" => (0x8, 0x8),
+            "Vector2" => (0x10, 0x8),
+            "Vector3" => (0x18, 0x8),
+            _ if unwrap_type_name(type_name, "Pointer").is_some()
+                || unwrap_type_name(type_name, "ClassPointer").is_some() =>
+            {
+                (0x8, 0x8)
+            }
+            // data, count, most
+            _ if unwrap_type_name(type_name, "DynArray").is_some() => (0x10, 0x8),
+            _ => return None,
+        };
+
+        Some(layout)
+    }
+}
+
+/// The name of the type inside a wrapper, e.g. `Vector3` for `Struct<Vector3>`
+fn unwrap_type_name<'n>(name: &'n str, wrapper: &str) -> Option<&'n str> {
+    name.strip_prefix(wrapper)?
+        .strip_prefix('<')?
+        .strip_suffix('>')
+}
+
+/// Split `K, V` (which may themselves have type arguments) at the top level comma.
+fn split_type_args(args: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => return Some((args[..i].trim(), args[i + 1..].trim())),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// An object (anything that inherits from `Object` in ZScript), which knows its own class and so
+/// can read its fields by name.
+///
+/// The class is borrowed, so the fields it finds are cached on the class itself (usually the one
+/// in `ZDoom::classes`) and shared by every object of that class.
+#[derive(Clone)]
+pub struct DObject<'z, 'a> {
+    process: &'a Process,
+    name_manager: Rc<NameManager<'a>>,
+    address: Address,
+    class: &'z PClass<'a>,
+}
+
+impl<'z, 'a> DObject<'z, 'a> {
+    /// Prefer `ZDoom::object`, which uses the already cached class.
+    pub fn new(
+        process: &'a Process,
+        name_manager: Rc<NameManager<'a>>,
+        address: Address,
+        class: &'z PClass<'a>,
+    ) -> DObject<'z, 'a> {
+        DObject {
+            process,
+            name_manager,
+            address,
+            class,
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn class(&self) -> &'z PClass<'a> {
+        self.class
+    }
+
+    /// The field, whether it's declared on this object's class or any superclass.
+    pub fn field(&self, name: &str) -> Result<&'z PField<'a>, Option<Error>> {
+        self.class.find_field(name)?.ok_or_else(|| {
+            debug!(
+                "{} has no field {name}",
+                self.class.name().map_or("?", |n| n)
+            );
+            None
+        })
+    }
+
+    /// Where the field is in this object.
+    pub fn field_address(&self, name: &str) -> Result<Address, Option<Error>> {
+        let field = self.field(name)?;
+        if field.flags()?.contains(PFieldFlags::Static) {
+            // statics don't live in the object
+            return Err(None);
+        }
+
+        Ok(self.address + *field.offset()? as u64)
+    }
+
+    /// Read the field and decode it according to its type.
+    pub fn get(&self, name: &str) -> Result<Value, Option<Error>> {
+        let address = self.field_address(name)?;
+        let ptype = self.field(name)?.ptype()?;
+        let type_name = ptype.name()?;
+        let flags = ptype.flags()?;
+        if flags.contains(TypeFlags::ObjectPointer) {
+            return Ok(Value::Object(self.process.read::<u64>(address)?.into()));
+        }
+
+        Value::read(self.process, &self.name_manager, type_name, address)
+    }
+
+    /// The elements of an array field.
+    pub fn get_array(&self, name: &str) -> Result<Vec<Value>, Option<Error>> {
+        match self.get(name)? {
+            Value::Array {
+                address,
+                element_type,
+            } => TArray::new(self.process, address).read_values(&self.name_manager, &element_type),
+            _ => Err(None),
+        }
+    }
+
+    /// The entries of a map field.
+    pub fn get_map(&self, name: &str) -> Result<Vec<(Value, Value)>, Option<Error>> {
+        match self.get(name)? {
+            Value::Map {
+                address,
+                key_type,
+                value_type,
+            } => TMap::new(self.process, address).read_values(
+                &self.name_manager,
+                &key_type,
+                &value_type,
+            ),
+            _ => Err(None),
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> Result<bool, Option<Error>> {
+        match self.get(name)? {
+            Value::Bool(v) => Ok(v),
+            _ => Err(None),
+        }
+    }
+
+    /// Any integer field that fits in an i32.
+    pub fn get_i32(&self, name: &str) -> Result<i32, Option<Error>> {
+        let value = self.get(name)?.as_i64().ok_or(None)?;
+        i32::try_from(value).map_err(|_| None)
+    }
+
+    /// Any integer field that fits in a u32.
+    pub fn get_u32(&self, name: &str) -> Result<u32, Option<Error>> {
+        let value = self.get(name)?.as_i64().ok_or(None)?;
+        u32::try_from(value).map_err(|_| None)
+    }
+
+    /// A float or double field.
+    pub fn get_f64(&self, name: &str) -> Result<f64, Option<Error>> {
+        self.get(name)?.as_f64().ok_or(None)
+    }
+
+    /// A string or name field.
+    pub fn get_string(&self, name: &str) -> Result<String, Option<Error>> {
+        match self.get(name)? {
+            Value::String(v) | Value::Name(v) => Ok(v),
+            _ => Err(None),
+        }
+    }
+
+    /// The address of the object the field points at, if any.
+    pub fn get_object(&self, name: &str) -> Result<Option<Address>, Option<Error>> {
+        match self.get(name)? {
+            Value::Object(address) if address == Address::NULL => Ok(None),
+            Value::Object(address) => Ok(Some(address)),
+            _ => Err(None),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use asr::{Address, Process};
+use helpers::{debug, info, warn};
+
+use crate::dobject::DObject;
+use crate::pclass::DOBJECT_CLASS;
+use crate::ZDoom;
+
+/// EventManager { Level, FirstEventHandler, LastEventHandler, ... }
+const EVENT_MANAGER_LEVEL: u64 = 0x0;
+const EVENT_MANAGER_FIRST: u64 = 0x8;
+const EVENT_MANAGER_LAST: u64 = 0x10;
+
+/// DStaticEventHandler { prev, next, ... } comes right after `DObject`'s members
+const HANDLER_PREV: u64 = 0x0;
+const HANDLER_NEXT: u64 = 0x8;
+
+const MAX_HANDLERS: usize = 0x400;
+
+/// Where to look for `FLevelLocals::localEventManager`
+const LOCAL_EVENT_MANAGER_SEARCH_SIZE: u64 = 0x4000;
+const SCAN_CHUNK_SIZE: u64 = 0x10000;
+
+fn looks_like_a_pointer(value: u64) -> bool {
+    value > 0x10000 && value < 0x8000_0000_0000 && value.is_multiple_of(0x8)
+}
+
+/// Find `localEventManager` in the level, a pointer to an `EventManager` that points back at the
+/// level, and whose first handler (if there is one) is one of `handler_classes`.
+pub(crate) fn probe_local_event_manager_offset(
+    process: &Process,
+    level_addr: Address,
+    handler_classes: &HashSet<Address>,
+) -> Option<u64> {
+    let is_manager = |manager: u64| {
+        process
+            .read::<u64>(manager + EVENT_MANAGER_LEVEL)
+            .is_ok_and(|level| level == level_addr.value())
+            && process
+                .read::<u64>(manager + EVENT_MANAGER_FIRST)
+                .is_ok_and(|first| {
+                    first == 0
+                        || (looks_like_a_pointer(first)
+                            && process
+                                .read::<u64>(first + DOBJECT_CLASS)
+                                .is_ok_and(|class| handler_classes.contains(&class.into())))
+                })
+    };
+
+    let offset = (0..LOCAL_EVENT_MANAGER_SEARCH_SIZE)
+        .step_by(0x8)
+        .find(|offset| {
+            process
+                .read::<u64>(level_addr + *offset)
+                .is_ok_and(|manager| looks_like_a_pointer(manager) && is_manager(manager))
+        });
+
+    match offset {
+        Some(offset) => info!("zdoom: Probed level_local_event_manager at 0x{offset:X}"),
+        None => warn!("zdoom: Couldn't find the level's event manager"),
+    }
+
+    offset
+}
+
+/// Whether there's an `EventManager` with no level (i.e. `staticEventManager`) at `address`, whose
+/// first handler is one of `handler_classes`. `links_offset` is where `prev` and `next` are in a
+/// handler.
+pub(crate) fn is_static_event_manager(
+    process: &Process,
+    address: Address,
+    handler_classes: &HashSet<Address>,
+    links_offset: u64,
+) -> bool {
+    let (Ok(level), Ok(first), Ok(last)) = (
+        process.read::<u64>(address + EVENT_MANAGER_LEVEL),
+        process.read::<u64>(address + EVENT_MANAGER_FIRST),
+        process.read::<u64>(address + EVENT_MANAGER_LAST),
+    ) else {
+        return false;
+    };
+
+    level == 0
+        && looks_like_a_pointer(first)
+        && looks_like_a_pointer(last)
+        && process
+            .read::<u64>(first + DOBJECT_CLASS)
+            .is_ok_and(|class| handler_classes.contains(&class.into()))
+        && process
+            .read::<u64>(first + links_offset + HANDLER_PREV)
+            .is_ok_and(|prev| prev == 0)
+}
+
+/// Look through the module for `staticEventManager`. This only works if a static handler has been
+/// registered, which happens when the game starts.
+pub(crate) fn scan_static_event_manager(
+    process: &Process,
+    (start, len): (Address, u64),
+    handler_classes: &HashSet<Address>,
+    links_offset: u64,
+) -> Result<Address, ()> {
+    let mut buf = vec![0; SCAN_CHUNK_SIZE as usize];
+    let mut offset = 0;
+    while offset < len {
+        let size = SCAN_CHUNK_SIZE.min(len - offset) as usize;
+        let chunk = &mut buf[..size];
+        if process.read_into_buf(start + offset, chunk).is_ok() {
+            let pointers = chunk
+                .chunks_exact(0x8)
+                .map(|p| u64::from_le_bytes(p.try_into().unwrap()))
+                .collect::<Vec<_>>();
+
+            // cheap checks on what we've already read, before reading anything else
+            for (i, manager) in pointers.windows(3).enumerate() {
+                if manager[0] == 0
+                    && looks_like_a_pointer(manager[1])
+                    && looks_like_a_pointer(manager[2])
+                {
+                    let address = start + offset + i as u64 * 0x8;
+                    if is_static_event_manager(process, address, handler_classes, links_offset) {
+                        return Ok(address);
+                    }
+                }
+            }
+        }
+
+        // overlap, so a manager straddling two chunks isn't missed
+        offset += SCAN_CHUNK_SIZE - 0x10;
+    }
+
+    Err(())
+}
+
+/// The handlers registered with the `EventManager` at `manager`, in the order they're called.
+pub(crate) fn read_handlers<'z, 'a>(
+    zdoom: &'z ZDoom<'a>,
+    manager: Address,
+    links_offset: u64,
+) -> Vec<DObject<'z, 'a>> {
+    let mut handlers = Vec::new();
+    let mut current = zdoom.process.read::<u64>(manager + EVENT_MANAGER_FIRST);
+
+    // count every step, not just the handlers we could read, so a loop of garbage still ends
+    for _ in 0..MAX_HANDLERS {
+        let Ok(handler) = current else {
+            break;
+        };
+        if handler == 0 {
+            break;
+        }
+
+        match zdoom.object(handler.into()) {
+            Ok(object) => handlers.push(object),
+            Err(_) => debug!("event handler at 0x{handler:X} has an unknown class"),
+        }
+
+        current = zdoom
+            .process
+            .read::<u64>(handler + links_offset + HANDLER_NEXT);
+    }
+
+    handlers
+}
@@ -0,0 +1,86 @@
+use asr::{Address, Error, Process};
+
+/// FStringData { Len, AllocLen, RefCount } sits right before the characters
+const FSTRING_LEN: u64 = 0xC;
+
+/// Anything longer is assumed to be a garbage header
+const MAX_FSTRING_LEN: u32 = 0x10000;
+const MAX_C_STRING_LEN: usize = 0x1000;
+
+const PAGE_SIZE: u64 = 0x1000;
+const CHUNK_SIZE: u64 = 0x80;
+
+/// ZDoom's `FString`, which is a pointer to the characters, with the length (and other data) in a
+/// header just before them.
+pub struct FString;
+
+impl FString {
+    /// Read the FString stored at `address`. Invalid UTF-8 is replaced rather than failing.
+    pub fn read(process: &Process, address: Address) -> Result<String, Error> {
+        let chars: Address = process.read::<u64>(address)?.into();
+        FString::read_chars(process, chars)
+    }
+
+    /// Whether there's an FString at `address`: the characters have a header with a sane length,
+    /// and end in a NUL right where it says. Empty strings point at a shared empty string, so
+    /// they're never null.
+    pub fn is_valid(process: &Process, address: Address) -> bool {
+        let Ok(chars) = process.read::<u64>(address) else {
+            return false;
+        };
+
+        chars != 0
+            && process
+                .read::<u32>(chars.wrapping_sub(FSTRING_LEN))
+                .is_ok_and(|len| {
+                    len <= MAX_FSTRING_LEN
+                        && process
+                            .read::<u8>(chars + len as u64)
+                            .is_ok_and(|end| end == 0)
+                })
+    }
+
+    /// Read an FString given the pointer to its characters.
+    pub fn read_chars(process: &Process, chars: Address) -> Result<String, Error> {
+        if chars == Address::NULL {
+            return Ok(String::new());
+        }
+
+        let len: u32 = process.read(chars.value().wrapping_sub(FSTRING_LEN))?;
+        if len > MAX_FSTRING_LEN {
+            // doesn't look like a real header, so fall back to reading up to the NUL
+            return read_c_string(process, chars);
+        }
+
+        let mut buf = vec![0; len as usize];
+        process.read_into_buf(chars, &mut buf)?;
+
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+}
+
+/// Read a NUL-terminated string of any (reasonable) length, a chunk at a time. Invalid UTF-8 is
+/// replaced rather than failing.
+pub(crate) fn read_c_string(process: &Process, address: Address) -> Result<String, Error> {
+    let mut bytes = Vec::new();
+    let mut current = address.value();
+
+    while bytes.len() < MAX_C_STRING_LEN {
+        // don't let a chunk cross a page boundary, the next page might not be mapped
+        let size = CHUNK_SIZE.min(PAGE_SIZE - current % PAGE_SIZE) as usize;
+        let mut chunk = [0; CHUNK_SIZE as usize];
+        let chunk = &mut chunk[..size];
+        process.read_into_buf(current, chunk)?;
+
+        if let Some(nul) = chunk.iter().position(|b| *b == 0) {
+            bytes.extend_from_slice(&chunk[..nul]);
+            break;
+        }
+
+        bytes.extend_from_slice(chunk);
+        current += size as u64;
+    }
+
+    bytes.truncate(MAX_C_STRING_LEN);
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
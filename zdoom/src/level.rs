@@ -1,9 +1,15 @@
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::name_manager::NameManager;
-use crate::pclass::PClass;
+use crate::fstring::FString;
+use crate::name_manager::{FName, NameManager};
+use crate::pclass::{
+    PClass, PField, DOBJECT_CLASS, PCLASS_TYPENAME, PFIELD_NAME, PTYPE_DESCRIPTIVE_NAME,
+};
 use crate::tarray::TArray;
-use asr::{print_message, string::ArrayCString, Address, Error, Process};
+use asr::{time::Duration, Address, Error, Process};
+use helpers::{info, warn};
+use once_cell::unsync::OnceCell;
 
 use super::Memory;
 
@@ -13,6 +19,59 @@ pub struct Level<'a> {
     name_manager: Rc<NameManager<'a>>,
     address: Address,
     _name: Option<String>,
+    /// the fields of the `LevelLocals` struct, which is what ZScript sees this as
+    fields: OnceCell<HashMap<String, PField<'a>>>,
+    /// the offsets of `STAT_FIELDS`, or `None` if one of them isn't an int
+    stat_offsets: OnceCell<Option<[u64; STAT_FIELDS.len()]>>,
+}
+
+/// The `LevelLocals` fields `Level::stats` reads, in the order of `LevelStats`. They're all ints.
+const STAT_FIELDS: [&str; 8] = [
+    "killed_monsters",
+    "total_monsters",
+    "found_items",
+    "total_items",
+    "found_secrets",
+    "total_secrets",
+    "maptime",
+    "totaltime",
+];
+
+/// How many tics there are in a second
+pub const TICRATE: u64 = 35;
+
+/// How long `tics` game tics take.
+pub fn tics_to_duration(tics: i32) -> Duration {
+    let tics = tics.max(0) as u64;
+    Duration::new(
+        (tics / TICRATE) as i64,
+        ((tics % TICRATE) * 1_000_000_000 / TICRATE) as i32,
+    )
+}
+
+/// What's shown on the automap and intermission screens.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LevelStats {
+    pub kills: i32,
+    pub total_kills: i32,
+    pub items: i32,
+    pub total_items: i32,
+    pub secrets: i32,
+    pub total_secrets: i32,
+    /// Tics spent in this level
+    pub maptime: i32,
+    /// Tics spent in the whole game (or hub)
+    pub totaltime: i32,
+}
+
+impl LevelStats {
+    pub fn map_time(&self) -> Duration {
+        tics_to_duration(self.maptime)
+    }
+
+    pub fn total_time(&self) -> Duration {
+        tics_to_duration(self.totaltime)
+    }
 }
 
 impl<'a> Level<'a> {
@@ -28,6 +87,8 @@ impl<'a> Level<'a> {
             name_manager,
             address,
             _name: None,
+            fields: OnceCell::new(),
+            stat_offsets: OnceCell::new(),
         }
     }
 
@@ -40,103 +101,208 @@ impl<'a> Level<'a> {
             return Ok(name);
         }
 
-        let c_str = self.process.read_pointer_path::<ArrayCString<128>>(
-            self.address,
-            asr::PointerSize::Bit64,
-            &[self.memory.offsets.level_mapname, 0x0],
+        let name = FString::read(
+            self.process,
+            self.address + self.memory.offsets.level_mapname,
         )?;
 
-        let name = c_str
-            .validate_utf8()
-            .unwrap_or_else(|_| panic!("name should always be utf-8. raw: {:?}", c_str.as_bytes()))
-            .to_owned();
-
         self._name = Some(name.clone());
 
         Ok(self._name.as_ref().unwrap())
     }
 
-    pub fn find_actor(&self, actor_name: &str) -> Result<Address, Option<Error>> {
+    /// Every actor linked into a sector's thing list. Use `ZDoom::thinkers` to also get
+    /// everything that isn't.
+    fn sector_actors(&self, actor_class: &PClass<'a>) -> Result<Vec<Address>, Option<Error>> {
+        let snext = *actor_class.find_field("snext")?.ok_or(None)?.offset()? as u64;
         let sectors = TArray::new(
             self.process,
             self.address + self.memory.offsets.level_sectors,
         );
 
-        for sector in sectors.iter_addr(0x310)? {
+        let mut actors = Vec::new();
+        for sector in sectors.iter_addr(self.memory.offsets.sector_size)? {
             let mut actor_next = sector + self.memory.offsets.sector_thinglist;
             while let Ok(actor) = self.process.read::<u64>(actor_next) {
                 if Address::from(actor) == Address::NULL {
                     break;
                 }
 
-                let class = self.process.read::<u64>(actor + 0x8)?.into();
-                let class = PClass::new(
-                    self.process,
-                    self.memory.clone(),
-                    self.name_manager.clone(),
-                    class,
-                );
-
-                let name = class.name()?;
-                if name == actor_name {
-                    return Ok(actor.into());
-                }
-
-                actor_next = Address::from(actor + 0x40);
+                actors.push(actor.into());
+                actor_next = Address::from(actor + snext);
             }
         }
 
-        Err(None)
+        Ok(actors)
     }
 
-    pub fn get_actor_names(&self, actor_class: &PClass<'a>) -> Result<Vec<String>, Error> {
-        let sectors = TArray::new(
-            self.process,
-            self.address + self.memory.offsets.level_sectors,
-        );
+    /// The kill, item and secret counts and the level time.
+    ///
+    /// `thinker_class` is needed to find the `LevelLocals` type, from the type of `Thinker.Level`.
+    pub fn stats(&self, thinker_class: &PClass<'a>) -> Result<LevelStats, Option<Error>> {
+        let offsets = self.stat_offsets(thinker_class)?.ok_or(None)?;
+        let [kills, total_kills, items, total_items, secrets, total_secrets, maptime, totaltime] =
+            offsets.map(|offset| self.process.read::<i32>(self.address + offset));
 
-        let mut actors = Vec::new();
+        Ok(LevelStats {
+            kills: kills?,
+            total_kills: total_kills?,
+            items: items?,
+            total_items: total_items?,
+            secrets: secrets?,
+            total_secrets: total_secrets?,
+            maptime: maptime?,
+            totaltime: totaltime?,
+        })
+    }
 
-        for sector in sectors.iter_addr(0x310)? {
-            let mut actor_next = sector + self.memory.offsets.sector_thinglist;
-            while let Ok(actor) = self.process.read::<u64>(actor_next) {
-                if Address::from(actor) == Address::NULL {
-                    break;
+    /// Where the `STAT_FIELDS` are. A fork could have changed their types, which we'd misread, so
+    /// if any isn't an `SInt4` this is `None` (and that's logged, once).
+    fn stat_offsets(
+        &self,
+        thinker_class: &PClass<'a>,
+    ) -> Result<Option<[u64; STAT_FIELDS.len()]>, Option<Error>> {
+        self.stat_offsets
+            .get_or_try_init(|| {
+                let fields = self.fields(thinker_class)?;
+                let mut offsets = [0; STAT_FIELDS.len()];
+                for (offset, name) in offsets.iter_mut().zip(STAT_FIELDS) {
+                    let field = fields.get(name).ok_or(None)?;
+                    let type_name = field.ptype()?.name()?;
+                    if type_name != "SInt4" {
+                        warn!("zdoom: LevelLocals.{name} is a {type_name}, not an SInt4");
+                        return Ok(None);
+                    }
+
+                    *offset = *field.offset()? as u64;
                 }
 
-                let class = self.process.read::<u64>(actor + 0x8)?.into();
-                let class = PClass::new(
+                Ok(Some(offsets))
+            })
+            .copied()
+    }
+
+    /// The fields of `LevelLocals`. Structs aren't in the class list, so this goes from the pointer
+    /// type of `Thinker.Level` to the struct type, and looks for the fields in that.
+    pub fn fields(
+        &self,
+        thinker_class: &PClass<'a>,
+    ) -> Result<&HashMap<String, PField<'a>>, Option<Error>> {
+        self.fields.get_or_try_init(|| {
+            let pointer_type = thinker_class.find_field("Level")?.ok_or(None)?.ptype()?;
+            let maptime = self.name_manager.find("maptime")?.ok_or(None)?;
+
+            let struct_type = (0..0x100)
+                .step_by(0x8)
+                .filter_map(|offset| self.process.read::<u64>(pointer_type.address + offset).ok())
+                .map(Address::from)
+                .find(|ptype| {
+                    FString::read(self.process, *ptype + PTYPE_DESCRIPTIVE_NAME)
+                        .is_ok_and(|name| name == "LevelLocals")
+                })
+                .ok_or_else(|| {
+                    warn!("zdoom: Couldn't find the LevelLocals type");
+                    None
+                })?;
+
+            // a TArray of fields, one of which is maptime
+            let has_maptime = |fields: &[u64]| {
+                fields.iter().any(|field| {
+                    FName::read(self.process, Address::from(*field) + PFIELD_NAME)
+                        .is_ok_and(|name| name == maptime)
+                })
+            };
+            let fields_offset = (0..0x200)
+                .step_by(0x8)
+                .find(|offset| {
+                    TArray::new(self.process, struct_type + *offset)
+                        .read::<u64>()
+                        .is_ok_and(|fields| has_maptime(&fields))
+                })
+                .ok_or_else(|| {
+                    warn!("zdoom: Couldn't find the fields of LevelLocals");
+                    None
+                })?;
+            info!("zdoom: Found the fields of LevelLocals at 0x{fields_offset:X}");
+
+            let mut fields = HashMap::new();
+            for field in TArray::new(self.process, struct_type + fields_offset).iter::<u64>()? {
+                let field = PField::new(
                     self.process,
                     self.memory.clone(),
                     self.name_manager.clone(),
-                    class,
+                    field.into(),
                 );
+                fields.insert(field.name()?.to_owned(), field);
+            }
 
-                let name = class.name()?;
-                actors.push(name.to_owned());
-
-                actor_next = Address::from(
-                    actor
-                        + actor_class
-                            .fields()
-                            .unwrap()
-                            .get("snext")
-                            .unwrap()
-                            .offset()
-                            .unwrap()
-                            .to_owned() as u64,
-                );
+            Ok(fields)
+        })
+    }
+
+    pub fn find_actor(
+        &self,
+        actor_class: &PClass<'a>,
+        actor_name: &str,
+    ) -> Result<Address, Option<Error>> {
+        // if the name doesn't exist, neither does a class with it
+        let actor_name = self.name_manager.find(actor_name)?.ok_or(None)?;
+
+        for actor in self.sector_actors(actor_class)? {
+            let class: Address = self.process.read::<u64>(actor + DOBJECT_CLASS)?.into();
+            if FName::read(self.process, class + PCLASS_TYPENAME)? == actor_name {
+                return Ok(actor);
             }
         }
 
+        Err(None)
+    }
+
+    pub fn get_actor_names(&self, actor_class: &PClass<'a>) -> Result<Vec<String>, Option<Error>> {
+        let mut actors = Vec::new();
+        for actor in self.sector_actors(actor_class)? {
+            let class: Address = self.process.read::<u64>(actor + DOBJECT_CLASS)?.into();
+            actors.push(
+                self.name_manager
+                    .get(FName::read(self.process, class + PCLASS_TYPENAME)?)?,
+            );
+        }
+
         Ok(actors)
     }
 
-    pub fn dump_actors(&self, actor_class: &PClass<'a>) -> Result<(), Error> {
-        print_message("Dumping actors...");
+    pub fn dump_actors(&self, actor_class: &PClass<'a>) -> Result<(), Option<Error>> {
+        info!("Dumping actors...");
         let actors = self.get_actor_names(actor_class)?;
-        actors.iter().for_each(|actor| asr::print_message(actor));
+        actors.iter().for_each(|actor| info!("{actor}"));
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tics_to_duration_is_exact_on_whole_seconds() {
+        assert_eq!(tics_to_duration(0), Duration::ZERO);
+        assert_eq!(tics_to_duration(35), Duration::seconds(1));
+        assert_eq!(tics_to_duration(35 * 60 * 60), Duration::seconds(60 * 60));
+    }
+
+    #[test]
+    fn tics_to_duration_rounds_down_partial_seconds() {
+        // a tic is 28571428.57 nanoseconds
+        assert_eq!(tics_to_duration(1), Duration::nanoseconds(28_571_428));
+        assert_eq!(
+            tics_to_duration(36),
+            Duration::seconds(1) + Duration::nanoseconds(28_571_428)
+        );
+    }
+
+    #[test]
+    fn tics_to_duration_clamps_negative_tics() {
+        assert_eq!(tics_to_duration(-35), Duration::ZERO);
+    }
+}
@@ -1,25 +1,50 @@
 use std::time::Duration;
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use asr::{print_message, signature::Signature, Address, Error, Process};
+use asr::file_format::elf;
+use asr::{print_message, signature::Signature, string::ArrayCString, Address, Error, Process};
 use bytemuck::CheckedBitPattern;
+use helpers::memory::{find_bytes, read_c_string, ScanCache};
+use helpers::{debug, info, warn};
 use once_cell::unsync::OnceCell;
 
 use self::{
-    level::Level, name_manager::NameManager, pclass::PClass, player::Player, tarray::TArray,
+    dobject::DObject,
+    events::{
+        is_static_event_manager, probe_local_event_manager_offset, read_handlers,
+        scan_static_event_manager,
+    },
+    fstring::FString,
+    level::{Level, LevelStats},
+    name_manager::NameManager,
+    pclass::{PClass, DOBJECT_CLASS},
+    player::Player,
+    tarray::TArray,
+    thinker::{probe_thinkers_offset, Thinkers, NUM_STATS},
 };
 
+pub mod dobject;
+pub mod events;
+pub mod fstring;
 pub mod level;
 pub mod name_manager;
 pub mod pclass;
 pub mod player;
 pub mod tarray;
+pub mod thinker;
 
 pub struct ZDoom<'a> {
     process: &'a Process,
     pub memory: Rc<Memory>,
     pub name_data: Rc<NameManager<'a>>,
     pub classes: OnceCell<HashMap<String, PClass<'a>>>,
+    class_names: OnceCell<HashMap<Address, String>>,
+    thinkers_offset: OnceCell<u64>,
+    local_event_manager_offset: OnceCell<u64>,
+    static_event_manager: OnceCell<Address>,
     _actor_class: OnceCell<PClass<'a>>,
 
     pub level: Level<'a>,
@@ -30,25 +55,31 @@ pub struct ZDoom<'a> {
 impl<'a> ZDoom<'a> {
     pub async fn wait_try_load<T, F>(
         process: &'a Process,
-        version: ZDoomVersion,
+        version: Option<ZDoomVersion>,
         main_module_name: &str,
         load_fn: F,
     ) -> (ZDoom<'a>, T)
     where
         F: Fn(&HashMap<String, PClass<'a>>) -> Result<T, Option<Error>>,
     {
-        asr::print_message(&format!("zdoom: Using version {version:?}"));
+        match version {
+            Some(version) => info!("zdoom: Using version {version:?}"),
+            None => info!("zdoom: Detecting version"),
+        }
         let cooldown = Duration::from_secs(3);
 
         let fail_action = || async {
-            print_message(&format!(
-                "try_load unsuccessful, waiting {}s...",
-                cooldown.as_secs()
-            ));
+            debug!("try_load unsuccessful, waiting {}s...", cooldown.as_secs());
             asr::future::sleep(cooldown).await;
         };
 
         loop {
+            let Some(version) = version.or_else(|| ZDoomVersion::detect(process, main_module_name))
+            else {
+                fail_action().await;
+                continue;
+            };
+
             let memory = Memory::new(process, version, main_module_name);
             if memory.is_err() {
                 fail_action().await;
@@ -72,6 +103,10 @@ impl<'a> ZDoom<'a> {
                 name_data,
                 level,
                 classes: OnceCell::new(),
+                class_names: OnceCell::new(),
+                thinkers_offset: OnceCell::new(),
+                local_event_manager_offset: OnceCell::new(),
+                static_event_manager: OnceCell::new(),
                 _actor_class: OnceCell::new(),
                 player: OnceCell::new(),
                 gameaction: OnceCell::new(),
@@ -80,14 +115,14 @@ impl<'a> ZDoom<'a> {
             let classes = zdoom.classes();
             // assert that we have the Actor class, we need it for Player shenanigans
             if classes.is_err() {
-                print_message("try_load: error loading classes");
+                debug!("try_load: error loading classes");
                 fail_action().await;
                 continue;
             }
 
             let classes = classes.unwrap();
             if !classes.contains_key("Actor") {
-                print_message("try_load: missing Actor class");
+                debug!("try_load: missing Actor class");
                 fail_action().await;
                 continue;
             }
@@ -95,12 +130,12 @@ impl<'a> ZDoom<'a> {
             let result = load_fn(classes);
 
             if result.is_err() {
-                print_message("try_load: error running load_fn");
+                debug!("try_load: error running load_fn");
                 fail_action().await;
                 continue;
             }
 
-            print_message("try_load successful!");
+            info!("try_load successful!");
             return (zdoom, result.unwrap());
         }
     }
@@ -138,6 +173,223 @@ impl<'a> ZDoom<'a> {
         Ok(self.classes()?.get(name))
     }
 
+    /// The (cached) class whose descriptor is at `address`.
+    pub fn class_at(&self, address: Address) -> Result<Option<&PClass<'a>>, Error> {
+        let class_names = self.class_names.get_or_try_init(|| {
+            self.classes().map(|classes| {
+                classes
+                    .iter()
+                    .map(|(name, class)| (class.address(), name.to_owned()))
+                    .collect::<HashMap<_, _>>()
+            })
+        })?;
+
+        Ok(class_names
+            .get(&address)
+            .and_then(|name| self.classes.get()?.get(name)))
+    }
+
+    /// The object at `address`, typed by the class it says it is.
+    pub fn object(&self, address: Address) -> Result<DObject<'_, 'a>, Option<Error>> {
+        if address == Address::NULL {
+            return Err(None);
+        }
+
+        let class_addr: Address = self.process.read::<u64>(address + DOBJECT_CLASS)?.into();
+        let class = self.class_at(class_addr)?.ok_or_else(|| {
+            debug!("no known class at 0x{class_addr} for object 0x{address}");
+            None
+        })?;
+
+        Ok(DObject::new(
+            self.process,
+            self.name_data.clone(),
+            address,
+            class,
+        ))
+    }
+
+    /// Every thinker in the level, or only those with the stat number `stat` (see zdoom's
+    /// statnums.h).
+    pub fn thinkers(&self, stat: Option<u64>) -> Result<Thinkers<'_, 'a>, Option<Error>> {
+        self.thinkers_filtered(stat, None)
+    }
+
+    /// The thinkers in the level that are `class_name` or inherit from it. It's `Err(None)` if
+    /// there's no class called that, so a typo doesn't look like an empty level.
+    pub fn thinkers_of_class(
+        &self,
+        class_name: &str,
+        stat: Option<u64>,
+    ) -> Result<Thinkers<'_, 'a>, Option<Error>> {
+        let mut classes = HashSet::new();
+        for class in self.classes()?.values() {
+            if class.is_descendant_of(class_name)? {
+                classes.insert(class.address());
+            }
+        }
+        // every class descends from itself, so this is only empty if it doesn't exist
+        if classes.is_empty() {
+            return Err(None);
+        }
+
+        self.thinkers_filtered(stat, Some(classes))
+    }
+
+    fn thinkers_filtered(
+        &self,
+        stat: Option<u64>,
+        classes: Option<HashSet<Address>>,
+    ) -> Result<Thinkers<'_, 'a>, Option<Error>> {
+        let next_offset = *self.find_class("Object")?.ok_or(None)?.size()? as u64;
+        // not found isn't cached, the level may not have been set up yet
+        let thinkers_offset =
+            *self
+                .thinkers_offset
+                .get_or_try_init(|| -> Result<_, Option<Error>> {
+                    let thinker_class = self.find_class("Thinker")?.ok_or(None)?;
+                    probe_thinkers_offset(
+                        self.process,
+                        self.memory.level_addr,
+                        thinker_class.address(),
+                        next_offset,
+                    )
+                    .ok_or(None)
+                })?;
+
+        let stats = match stat {
+            Some(stat) if stat < NUM_STATS => stat..stat + 1,
+            Some(_) => return Err(None),
+            None => 0..NUM_STATS,
+        };
+
+        let mut sentinels = Vec::new();
+        for stat in stats {
+            let sentinel: Address = self
+                .process
+                .read::<u64>(self.memory.level_addr + thinkers_offset + stat * 0x8)?
+                .into();
+            if sentinel != Address::NULL {
+                sentinels.push(sentinel);
+            }
+        }
+
+        Ok(Thinkers::new(self, next_offset, sentinels, classes))
+    }
+
+    /// The current level's kill, item and secret counts and time.
+    pub fn level_stats(&self) -> Result<LevelStats, Option<Error>> {
+        let thinker_class = self.find_class("Thinker")?.ok_or(None)?;
+        self.level.stats(thinker_class)
+    }
+
+    /// The event handlers registered for the current level, in the order they're called.
+    pub fn event_handlers(&self) -> Result<Vec<DObject<'_, 'a>>, Option<Error>> {
+        // not found isn't cached, the level may not have been set up yet
+        let offset = *self.local_event_manager_offset.get_or_try_init(|| {
+            probe_local_event_manager_offset(
+                self.process,
+                self.memory.level_addr,
+                &self.static_event_handler_classes()?,
+            )
+            .ok_or(None)
+        })?;
+        let manager: Address = self
+            .process
+            .read::<u64>(self.memory.level_addr + offset)?
+            .into();
+        if manager == Address::NULL {
+            return Ok(Vec::new());
+        }
+
+        Ok(read_handlers(self, manager, self.event_handler_links()?))
+    }
+
+    /// The static event handlers, which live for the whole game, in the order they're called.
+    ///
+    /// The manager can only be found once there's a handler registered, so until then this is
+    /// `Err(None)`, and every call looks again (which may scan the whole module).
+    pub fn static_event_handlers(&self) -> Result<Vec<DObject<'_, 'a>>, Option<Error>> {
+        let links_offset = self.event_handler_links()?;
+        let manager = self.static_event_manager.get_or_try_init(|| {
+            let handler_classes = self.static_event_handler_classes()?;
+            let is_valid =
+                |addr| is_static_event_manager(self.process, addr, &handler_classes, links_offset);
+
+            if let Some(addr) = self
+                .memory
+                .static_event_manager_symbol
+                .filter(|a| is_valid(*a))
+            {
+                return Ok::<_, Option<Error>>(addr);
+            }
+
+            let scan = || {
+                scan_static_event_manager(
+                    self.process,
+                    self.memory.module_range,
+                    &handler_classes,
+                    links_offset,
+                )
+            };
+            let manager = match ScanCache::new(self.process, &self.memory.main_module_name) {
+                Ok(cache) => cache.resolve("static_event_manager", is_valid, scan),
+                Err(_) => scan(),
+            };
+
+            match manager {
+                Ok(addr) => {
+                    info!("zdoom: Found staticEventManager at 0x{addr}");
+                    Ok(addr)
+                }
+                Err(_) => {
+                    debug!("zdoom: Couldn't find staticEventManager");
+                    Err(None)
+                }
+            }
+        })?;
+
+        Ok(read_handlers(self, *manager, links_offset))
+    }
+
+    /// The event handler (static or for the current level) of class `class_name`, which is case
+    /// insensitive like in ZScript.
+    pub fn find_event_handler(&self, class_name: &str) -> Result<DObject<'_, 'a>, Option<Error>> {
+        let is_match = |handler: &DObject| {
+            handler
+                .class()
+                .name()
+                .is_ok_and(|n| n.eq_ignore_ascii_case(class_name))
+        };
+
+        if let Some(handler) = self.event_handlers()?.into_iter().find(is_match) {
+            return Ok(handler);
+        }
+
+        self.static_event_handlers()?
+            .into_iter()
+            .find(is_match)
+            .ok_or(None)
+    }
+
+    /// Every class of event handler, static or not (`EventHandler` inherits from
+    /// `StaticEventHandler`).
+    fn static_event_handler_classes(&self) -> Result<HashSet<Address>, Option<Error>> {
+        let mut handler_classes = HashSet::new();
+        for class in self.classes()?.values() {
+            if class.is_descendant_of("StaticEventHandler")? {
+                handler_classes.insert(class.address());
+            }
+        }
+
+        Ok(handler_classes)
+    }
+
+    /// Where a handler's `prev` and `next` are, right after `DObject`'s members.
+    fn event_handler_links(&self) -> Result<u64, Option<Error>> {
+        Ok(*self.find_class("Object")?.ok_or(None)?.size()? as u64)
+    }
+
     pub fn dump(&self) -> Result<(), Error> {
         print_message(
             r"#include <cstdint>
@@ -189,31 +441,279 @@ class TArray
 
 // disclaimer: I don't know much about the different zdoom versions work...
 // i have only tried this with a few games
-#[derive(Clone, Copy, Debug)]
+//
+// the versions after 4.8.2 are experimental, they haven't been checked against a game yet. their
+// offsets start from the closest version we know, and are probed (and corrected, if they look
+// wrong) when loading. the sector offsets can't be probed, so they're refused if those don't hold up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ZDoomVersion {
     Lzdoom3_82,   // Dismantled: Director's Cut
     Gzdoom4_8Pre, // Selaco
     Gzdoom4_8_2,  // Snap the Sentinel
+    Gzdoom4_10,
+    Gzdoom4_11,
+    Gzdoom4_12,
+    Gzdoom4_13,
+    Gzdoom4_14,
+    Lzdoom4, // LZDoom 4.x, which is based on GZDoom 4.x
+    Vkdoom,
+}
+
+impl ZDoomVersion {
+    /// The version banners the engines keep in their module, followed by the version, e.g.
+    /// "GZDoom g4.8.2".
+    const BANNERS: &'static [&'static str] = &["GZDoom ", "LZDoom ", "VKDoom "];
+
+    /// Work out the version from the engine's version banner.
+    ///
+    /// Games built on a fork of the engine may have changed the banner, or report a version we
+    /// don't have offsets for, in which case this is `None`, and the version has to be given to
+    /// `ZDoom::wait_try_load` instead.
+    pub fn detect(process: &Process, main_module_name: &str) -> Option<ZDoomVersion> {
+        let Ok((start, len)) = process.get_module_range(main_module_name) else {
+            warn!(
+                "zdoom: Couldn't get the range of {main_module_name}, pass the version explicitly"
+            );
+            return None;
+        };
+        let end = start + len;
+
+        // the banner can also be part of other strings (e.g. messages mentioning the engine), so
+        // keep looking until one of them is a version
+        let mut unknown = Vec::new();
+        for banner in Self::BANNERS {
+            let mut from = start;
+            while let Some(found_at) = find_bytes(process, (from, end - from), banner.as_bytes()) {
+                from = found_at + 1_u64;
+                let Ok(found) = read_c_string(process, found_at, 64) else {
+                    continue;
+                };
+
+                match Self::from_banner(&found) {
+                    Some(version) => {
+                        info!("zdoom: Detected {version:?} from {found:?}");
+                        return Some(version);
+                    }
+                    None => unknown.push(found),
+                }
+            }
+        }
+
+        if unknown.is_empty() {
+            warn!("zdoom: Couldn't find a version banner, pass the version explicitly");
+        } else {
+            warn!("zdoom: Unknown version in {unknown:?}, pass the version explicitly");
+        }
+
+        None
+    }
+
+    fn from_banner(banner: &str) -> Option<ZDoomVersion> {
+        let (engine, version) = banner.split_once(' ')?;
+        let version = version.strip_prefix('g').unwrap_or(version);
+
+        let numbers_end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let (numbers, suffix) = version.split_at(numbers_end);
+        let numbers = numbers
+            .split('.')
+            .map(|n| n.parse::<u32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let is_pre = suffix.starts_with("pre");
+
+        match (engine, numbers.as_slice(), is_pre) {
+            ("LZDoom", [3, 82, ..], _) => Some(ZDoomVersion::Lzdoom3_82),
+            ("GZDoom", [4, 8], true) => Some(ZDoomVersion::Gzdoom4_8Pre),
+            ("GZDoom", [4, 8, 2], _) => Some(ZDoomVersion::Gzdoom4_8_2),
+            ("GZDoom", [4, 10, ..], _) => Some(ZDoomVersion::Gzdoom4_10),
+            ("GZDoom", [4, 11, ..], _) => Some(ZDoomVersion::Gzdoom4_11),
+            ("GZDoom", [4, 12, ..], _) => Some(ZDoomVersion::Gzdoom4_12),
+            ("GZDoom", [4, 13, ..], _) => Some(ZDoomVersion::Gzdoom4_13),
+            ("GZDoom", [4, 14, ..], _) => Some(ZDoomVersion::Gzdoom4_14),
+            ("LZDoom", [4, ..], _) => Some(ZDoomVersion::Lzdoom4),
+            ("VKDoom", _, _) => Some(ZDoomVersion::Vkdoom),
+            _ => None,
+        }
+    }
+
+    /// Whether the offsets for this version have been checked against a real game. The rest are
+    /// copies of the 4.8.2 table, which are probed when loading, and only used if the sector
+    /// offsets hold up in the game (see `Offsets::sectors_hold_up`).
+    ///
+    /// This is spelled out per version, so a new one has to be added here deliberately.
+    fn offsets_are_known(&self) -> bool {
+        match self {
+            ZDoomVersion::Lzdoom3_82 | ZDoomVersion::Gzdoom4_8Pre | ZDoomVersion::Gzdoom4_8_2 => {
+                true
+            }
+            ZDoomVersion::Gzdoom4_10
+            | ZDoomVersion::Gzdoom4_11
+            | ZDoomVersion::Gzdoom4_12
+            | ZDoomVersion::Gzdoom4_13
+            | ZDoomVersion::Gzdoom4_14
+            | ZDoomVersion::Lzdoom4
+            | ZDoomVersion::Vkdoom => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Platform {
+    Windows,
+    Linux,
+}
+
+impl Platform {
+    fn detect(process: &Process, module_address: Address) -> Platform {
+        match process.read::<[u8; 4]>(module_address) {
+            Ok(magic) if &magic == b"\x7FELF" => Platform::Linux,
+            _ => Platform::Windows,
+        }
+    }
+}
+
+/// The (mangled) names of the globals we need, for Linux builds that still have their symbols.
+/// 4.x keeps a pointer to the level in `primaryLevel`, older versions have the level in `level`.
+const SYMBOL_NAMES: &[&str] = &[
+    "_ZN5FName8NameDataE",
+    "players",
+    "_ZN6PClass10AllClassesE",
+    "primaryLevel",
+    "level",
+    "gameaction",
+    "staticEventManager",
+];
+
+/// The symbols we can't do without on linux, where any one name of each group will do.
+const REQUIRED_SYMBOLS: &[&[&str]] = &[
+    &["_ZN5FName8NameDataE"],
+    &["players"],
+    &["_ZN6PClass10AllClassesE"],
+    &["primaryLevel", "level"],
+    &["gameaction"],
+];
+
+fn read_symbols(process: &Process, module_address: Address) -> HashMap<String, Address> {
+    elf::symbols(process, module_address)
+        .filter_map(|symbol| {
+            let name = symbol.get_name::<64>(process).ok()?;
+            let name = name.validate_utf8().ok()?;
+
+            SYMBOL_NAMES
+                .contains(&name)
+                .then(|| (name.to_owned(), symbol.address))
+        })
+        .collect()
 }
 
 type ScanFn = fn(process: &Process, module_range: (Address, u64)) -> Result<Address, Option<Error>>;
 
-fn find_addr_or_panic(
+type SanityFn<'f> = &'f dyn Fn(&Process, Address) -> bool;
+
+/// Find a global from its symbol, the scan cache or the signatures, in that order. Not finding it
+/// is `Err(None)`, so loading is retried (the game may not have set it up yet).
+fn find_addr(
     name: &str,
     process: &Process,
     module_range: (Address, u64),
+    cache: Option<&ScanCache>,
+    symbol: Option<Address>,
+    is_valid: SanityFn<'_>,
     sigs: Vec<ScanFn>,
-) -> Address {
-    for (i, sig) in sigs.iter().enumerate() {
-        if let Ok(addr) = sig(process, module_range) {
-            asr::print_message(&format!(
-                "Found {name} at 0x{addr} with signature index {i}"
-            ));
-            return addr;
+) -> Result<Address, Option<Error>> {
+    if let Some(addr) = symbol {
+        if is_valid(process, addr) {
+            debug!("Found {name} at 0x{addr} from its symbol");
+            return Ok(addr);
         }
+
+        warn!("Symbol for {name} points at 0x{addr}, which doesn't look right");
     }
 
-    panic!("unable to find addr for {name}");
+    let scan = || {
+        for (i, sig) in sigs.iter().enumerate() {
+            let Ok(addr) = sig(process, module_range) else {
+                continue;
+            };
+
+            // a signature can match in a build it wasn't written for, and land somewhere else
+            if !is_valid(process, addr) {
+                warn!("Signature index {i} for {name} found 0x{addr}, which doesn't look right");
+                continue;
+            }
+
+            debug!("Found {name} at 0x{addr} with signature index {i}");
+            return Ok(addr);
+        }
+
+        Err(())
+    };
+
+    let addr = match cache {
+        Some(cache) => cache.resolve(name, |addr| is_valid(process, addr), scan),
+        None => scan(),
+    };
+
+    addr.map_err(|_| {
+        warn!("zdoom: Unable to find {name}");
+        None
+    })
+}
+
+// cheap checks to tell if an address (scanned or cached) points at what we think it does
+
+fn namedata_is_valid(process: &Process, addr: Address) -> bool {
+    // the first name is always "None". read it raw, since this may be pointing at garbage
+    process
+        .read_pointer_path::<ArrayCString<8>>(addr, asr::PointerSize::Bit64, &[0x8, 0x0, 0x0])
+        .is_ok_and(|name| name.as_bytes() == b"None")
+}
+
+fn players_is_valid(process: &Process, addr: Address) -> bool {
+    // players[0].mo, which is either null or a pointer to an actor (with its class at 0x8)
+    match process.read::<u64>(addr) {
+        Ok(0) => true,
+        Ok(mo) => process.read::<u64>(mo + 0x8).is_ok_and(|class| class != 0),
+        Err(_) => false,
+    }
+}
+
+fn all_classes_is_valid(process: &Process, addr: Address) -> bool {
+    let count = process.read::<u32>(addr + 0x8_u64);
+    let most = process.read::<u32>(addr + 0xC_u64);
+
+    // empty before the classes are registered, which is fine as long as the header is sane
+    matches!((count, most), (Ok(count), Ok(most)) if count <= most && most < 0x10_0000)
+}
+
+/// `check_mapname` is for when `level_mapname` comes from a table that's been checked, otherwise
+/// it's only probed once the level has been found.
+fn level_is_valid(
+    process: &Process,
+    addr: Address,
+    offsets: &Offsets,
+    check_mapname: bool,
+) -> bool {
+    if check_mapname && !FString::is_valid(process, addr + offsets.level_mapname) {
+        return false;
+    }
+
+    // the sectors TArray has a sane header, and if there are sectors they're somewhere
+    let sectors = addr + offsets.level_sectors;
+    let (Ok(data), Ok(count), Ok(most)) = (
+        process.read::<u64>(sectors),
+        process.read::<u32>(sectors + 0x8_u64),
+        process.read::<u32>(sectors + 0xC_u64),
+    ) else {
+        return false;
+    };
+
+    count <= most && count < 0x10_0000 && (count == 0 || (data != 0 && data.is_multiple_of(0x8)))
+}
+
+fn gameaction_is_valid(process: &Process, addr: Address) -> bool {
+    process.read::<GameAction>(addr).is_ok()
 }
 
 fn scan<const N: usize>(
@@ -237,7 +737,10 @@ pub struct Memory {
     all_classes_addr: Address,
     level_addr: Address,
     gameaction_addr: Address,
+    static_event_manager_symbol: Option<Address>,
 
+    main_module_name: String,
+    module_range: (Address, u64),
     offsets: Offsets,
 }
 
@@ -246,8 +749,58 @@ impl Memory {
         process: &Process,
         version: ZDoomVersion,
         main_module_name: &str,
-    ) -> Result<Memory, Error> {
+    ) -> Result<Memory, Option<Error>> {
         let module_range = process.get_module_range(main_module_name)?;
+        let cache = ScanCache::new(process, main_module_name).ok();
+        let cache = cache.as_ref();
+
+        // the signatures are all for the MSVC builds, on linux we rely on symbols
+        let platform = Platform::detect(process, module_range.0);
+        let symbols = match platform {
+            Platform::Linux => read_symbols(process, module_range.0),
+            Platform::Windows => HashMap::new(),
+        };
+        debug!(
+            "zdoom: {platform:?} build, found symbols {:?}",
+            symbols.keys()
+        );
+        let symbol = |name: &str| symbols.get(name).copied();
+
+        // there are no gcc signatures, so a stripped build is a dead end
+        if platform == Platform::Linux {
+            let missing = REQUIRED_SYMBOLS
+                .iter()
+                .filter(|names| !names.iter().any(|name| symbols.contains_key(*name)))
+                .map(|names| names.join(" or "))
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                warn!(
+                    "zdoom: This linux build is missing the symbols {missing:?}, stripped builds aren't supported"
+                );
+                return Err(None);
+            }
+        }
+
+        // primaryLevel is a pointer, which is null until the game has set up the level
+        let level_symbol = match symbol("primaryLevel") {
+            Some(addr) => match process.read::<u64>(addr) {
+                Ok(0) | Err(_) => {
+                    debug!("zdoom: primaryLevel isn't set yet");
+                    return Err(None);
+                }
+                Ok(level) => Some(Address::from(level)),
+            },
+            None => symbol("level"),
+        };
+
+        let offsets = Offsets::new(version);
+        // gcc may lay things out differently to msvc, so don't trust the tables on linux
+        let tables_are_checked = version.offsets_are_known() && platform != Platform::Linux;
+
+        let msvc_only = |sigs: Vec<ScanFn>| match platform {
+            Platform::Windows => sigs,
+            Platform::Linux => Vec::new(),
+        };
 
         let namedata_sigs: Vec<ScanFn> = vec![
             |p, mr| {
@@ -334,22 +887,79 @@ impl Memory {
         }];
 
         Ok(Memory {
-            namedata_addr: find_addr_or_panic("namedata", process, module_range, namedata_sigs),
-            players_addr: find_addr_or_panic("players", process, module_range, players_sigs),
-            all_classes_addr: find_addr_or_panic(
+            namedata_addr: find_addr(
+                "namedata",
+                process,
+                module_range,
+                cache,
+                symbol("_ZN5FName8NameDataE"),
+                &namedata_is_valid,
+                msvc_only(namedata_sigs),
+            )?,
+            players_addr: find_addr(
+                "players",
+                process,
+                module_range,
+                cache,
+                symbol("players"),
+                &players_is_valid,
+                msvc_only(players_sigs),
+            )?,
+            all_classes_addr: find_addr(
                 "all_classes",
                 process,
                 module_range,
-                all_classes_sigs,
-            ),
-            level_addr: find_addr_or_panic("level", process, module_range, level_sigs),
-            gameaction_addr: find_addr_or_panic(
+                cache,
+                symbol("_ZN6PClass10AllClassesE"),
+                &all_classes_is_valid,
+                msvc_only(all_classes_sigs),
+            )?,
+            level_addr: find_addr(
+                "level",
+                process,
+                module_range,
+                cache,
+                level_symbol,
+                &|process, addr| level_is_valid(process, addr, &offsets, tables_are_checked),
+                msvc_only(level_sigs),
+            )?,
+            gameaction_addr: find_addr(
                 "gameaction",
                 process,
                 module_range,
-                gameaction_sigs,
-            ),
-            offsets: Offsets::new(version),
+                cache,
+                symbol("gameaction"),
+                &gameaction_is_valid,
+                msvc_only(gameaction_sigs),
+            )?,
+            static_event_manager_symbol: symbol("staticEventManager"),
+            main_module_name: main_module_name.to_owned(),
+            module_range,
+            offsets,
+        })
+        .and_then(|mut memory| {
+            let (all_classes_addr, level_addr) = (memory.all_classes_addr, memory.level_addr);
+            if !version.offsets_are_known() {
+                warn!("zdoom: {version:?} is experimental, its offsets are guesses");
+            }
+            if !tables_are_checked {
+                memory.offsets.probe(process, all_classes_addr, level_addr);
+            }
+
+            // the sector offsets can't be probed, so only go ahead with a guessed table (or one
+            // from windows, on linux) if they hold up in the current level
+            if !tables_are_checked
+                && !memory
+                    .offsets
+                    .sectors_hold_up(process, all_classes_addr, level_addr)
+            {
+                warn!(
+                    "zdoom: The sector offsets for {version:?} ({platform:?}) don't match this game"
+                );
+                return Err(None);
+            }
+
+            Ok(memory)
         })
     }
 }
@@ -358,6 +968,7 @@ struct Offsets {
     pclass_fields: u64,
     level_mapname: u64,
     level_sectors: u64,
+    sector_size: u64,
     sector_thinglist: u64,
 }
 
@@ -368,22 +979,193 @@ impl Offsets {
                 pclass_fields: 0x78,
                 level_mapname: 0x2C0,
                 level_sectors: 0x10,
+                sector_size: 0x310,
                 sector_thinglist: 0x180,
             },
             ZDoomVersion::Gzdoom4_8Pre => Self {
                 pclass_fields: 0x80,
                 level_mapname: 0x9F8,
                 level_sectors: 0x50,
+                sector_size: 0x310,
                 sector_thinglist: 0x268,
             },
-            ZDoomVersion::Gzdoom4_8_2 => Self {
+            // the experimental versions (see `ZDoomVersion`) start from 4.8.2
+            ZDoomVersion::Gzdoom4_8_2
+            | ZDoomVersion::Gzdoom4_10
+            | ZDoomVersion::Gzdoom4_11
+            | ZDoomVersion::Gzdoom4_12
+            | ZDoomVersion::Gzdoom4_13
+            | ZDoomVersion::Gzdoom4_14
+            | ZDoomVersion::Lzdoom4
+            | ZDoomVersion::Vkdoom => Self {
                 pclass_fields: 0x78,
                 level_mapname: 0x9D8,
                 level_sectors: 0x50,
+                sector_size: 0x310,
                 sector_thinglist: 0x268,
             },
         }
     }
+
+    /// Check the offsets we're unsure of against the game, and look around for better ones if
+    /// they don't hold up. Anything we can't find stays as it was.
+    ///
+    /// `level_sectors`, `sector_size` and `sector_thinglist` aren't probed, there's nothing cheap
+    /// to recognise them by. See `sectors_hold_up`.
+    fn probe(&mut self, process: &Process, all_classes_addr: Address, level_addr: Address) {
+        match probe_offset(self.pclass_fields, (0x40..=0xC0).step_by(8), |offset| {
+            pclass_fields_score(process, all_classes_addr, offset)
+        }) {
+            Some(offset) if offset != self.pclass_fields => {
+                info!("zdoom: Probed pclass_fields at 0x{offset:X}");
+                self.pclass_fields = offset;
+            }
+            Some(_) => {}
+            None => warn!(
+                "zdoom: Couldn't verify pclass_fields 0x{:X}",
+                self.pclass_fields
+            ),
+        }
+
+        match probe_offset(self.level_mapname, (0x200..=0xE00).step_by(8), |offset| {
+            level_mapname_score(process, level_addr, offset)
+        }) {
+            Some(offset) if offset != self.level_mapname => {
+                info!("zdoom: Probed level_mapname at 0x{offset:X}");
+                self.level_mapname = offset;
+            }
+            Some(_) => {}
+            None => warn!(
+                "zdoom: Couldn't verify level_mapname 0x{:X}",
+                self.level_mapname
+            ),
+        }
+    }
+
+    /// Whether the sector offsets work in the current level: the sectors array has a sane header,
+    /// and the things in the first sectors that have any are all objects of a class we know.
+    ///
+    /// This needs a level with things in it, so it fails (and loading is retried) before one is
+    /// loaded.
+    fn sectors_hold_up(
+        &self,
+        process: &Process,
+        all_classes_addr: Address,
+        level_addr: Address,
+    ) -> bool {
+        let Ok(classes) = TArray::new(process, all_classes_addr).read::<u64>() else {
+            return false;
+        };
+        let classes = classes.into_iter().collect::<HashSet<_>>();
+        let Ok(sectors) =
+            TArray::new(process, level_addr + self.level_sectors).iter_addr(self.sector_size)
+        else {
+            return false;
+        };
+
+        let mut things = 0;
+        for sector in sectors.take(256) {
+            let Ok(thing) = process.read::<u64>(sector + self.sector_thinglist) else {
+                return false;
+            };
+            if thing == 0 {
+                continue;
+            }
+
+            let is_object = process
+                .read::<u64>(thing + DOBJECT_CLASS)
+                .is_ok_and(|class| classes.contains(&class));
+            if !is_object {
+                return false;
+            }
+
+            things += 1;
+        }
+
+        things > 0
+    }
+}
+
+/// Keep `current` if it scores, otherwise take the best scoring candidate.
+fn probe_offset(
+    current: u64,
+    candidates: impl Iterator<Item = u64>,
+    score: impl Fn(u64) -> u32,
+) -> Option<u64> {
+    if score(current) > 0 {
+        return Some(current);
+    }
+
+    candidates
+        .map(|offset| (offset, score(offset)))
+        .filter(|(_, score)| *score > 0)
+        .max_by_key(|(offset, score)| (*score, std::cmp::Reverse(*offset)))
+        .map(|(offset, _)| offset)
+}
+
+/// How many of the first classes have something that looks like a TArray of fields at `offset`:
+/// a sane count, and fields whose offsets fit in the class.
+fn pclass_fields_score(process: &Process, all_classes_addr: Address, offset: u64) -> u32 {
+    let Ok(classes) = TArray::new(process, all_classes_addr).iter::<u64>() else {
+        return 0;
+    };
+
+    let mut score = 0;
+    for class in classes.take(256) {
+        let class = Address::from(class);
+        let Ok(class_size) = process.read::<u32>(class + pclass::PCLASS_SIZE) else {
+            continue;
+        };
+
+        let fields = class + offset;
+        let (Ok(count), Ok(most)) = (
+            process.read::<u32>(fields + 0x8_u64),
+            process.read::<u32>(fields + 0xC_u64),
+        ) else {
+            continue;
+        };
+        if count == 0 || count > most || most > 0x1000 {
+            continue;
+        }
+
+        let Ok(field_addrs) = TArray::new(process, fields).iter::<u64>() else {
+            continue;
+        };
+        let fields_fit = field_addrs.take(count as usize).all(|field| {
+            process
+                .read::<u32>(Address::from(field) + pclass::PFIELD_OFFSET)
+                .is_ok_and(|field_offset| field_offset < class_size)
+        });
+
+        if fields_fit {
+            score += 1;
+        }
+    }
+
+    score
+}
+
+/// Whether there's an FString at `offset` that looks like a map name. The length in an FString's
+/// header (0xC before the characters) has to match the string.
+fn level_mapname_score(process: &Process, level_addr: Address, offset: u64) -> u32 {
+    let Ok(chars) = process.read::<u64>(level_addr + offset) else {
+        return 0;
+    };
+    let (Ok(name), Ok(len)) = (
+        process.read::<ArrayCString<32>>(chars),
+        process.read::<u32>(chars.wrapping_sub(0xC)),
+    ) else {
+        return 0;
+    };
+
+    let name = name.as_bytes();
+    let looks_like_a_name = !name.is_empty()
+        && name.len() == len as usize
+        && name
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || *c == b'_' || *c == b'-');
+
+    looks_like_a_name as u32
 }
 
 #[derive(CheckedBitPattern, Clone, Copy, Debug, Default, PartialEq)]
@@ -413,3 +1195,60 @@ pub enum GameAction {
     Intermission,
     TitleLoop,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_banner_reads_known_versions() {
+        let cases = [
+            ("LZDoom 3.82", ZDoomVersion::Lzdoom3_82),
+            ("LZDoom 3.82a", ZDoomVersion::Lzdoom3_82),
+            ("GZDoom g4.8pre-1234-gabcdef", ZDoomVersion::Gzdoom4_8Pre),
+            ("GZDoom g4.8.2", ZDoomVersion::Gzdoom4_8_2),
+            ("GZDoom g4.10.0", ZDoomVersion::Gzdoom4_10),
+            ("GZDoom g4.11.3", ZDoomVersion::Gzdoom4_11),
+            ("GZDoom g4.12.2", ZDoomVersion::Gzdoom4_12),
+            ("GZDoom g4.13.0", ZDoomVersion::Gzdoom4_13),
+            ("GZDoom g4.14.1", ZDoomVersion::Gzdoom4_14),
+            ("LZDoom 4.14.3", ZDoomVersion::Lzdoom4),
+            ("VKDoom 1.0.0", ZDoomVersion::Vkdoom),
+        ];
+
+        for (banner, version) in cases {
+            assert_eq!(ZDoomVersion::from_banner(banner), Some(version), "{banner}");
+        }
+    }
+
+    #[test]
+    fn from_banner_rejects_unknown_versions() {
+        for banner in [
+            "GZDoom g4.8.1",
+            "GZDoom g4.9.0",
+            "GZDoom g5.0.0",
+            "LZDoom 3.87",
+            "GZDoom",
+            "GZDoom ",
+            "GZDoom g.",
+            "QZDoom 2.1.0",
+        ] {
+            assert_eq!(ZDoomVersion::from_banner(banner), None, "{banner}");
+        }
+    }
+
+    #[test]
+    fn only_checked_versions_skip_the_sector_check() {
+        for version in [
+            ZDoomVersion::Gzdoom4_10,
+            ZDoomVersion::Gzdoom4_11,
+            ZDoomVersion::Gzdoom4_12,
+            ZDoomVersion::Gzdoom4_13,
+            ZDoomVersion::Gzdoom4_14,
+            ZDoomVersion::Lzdoom4,
+            ZDoomVersion::Vkdoom,
+        ] {
+            assert!(!version.offsets_are_known(), "{version:?}");
+        }
+    }
+}
@@ -1,30 +1,105 @@
-use asr::{string::ArrayCString, Address, Error, Process};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
+use asr::{Address, Error, Process};
+
+use crate::fstring::read_c_string;
+
+const NAME_ARRAY: u64 = 0x8;
+const NUM_NAMES: u64 = 0x10;
 const NAME_ENTRY_SIZE: u64 = 0x10;
 
+/// ZDoom's `FName`, an index into the name table. Two names are equal iff their indices are, so
+/// compare these instead of the strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FName(pub u32);
+
+impl FName {
+    pub fn read(process: &Process, address: Address) -> Result<FName, Error> {
+        Ok(FName(process.read(address)?))
+    }
+
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The table of all names. Names are never removed from it, so everything we read is cached for
+/// good.
 pub struct NameManager<'a> {
     process: &'a Process,
     address: Address,
+
+    names: RefCell<HashMap<u32, String>>,
+    /// keyed by the lowercased name, since names are case insensitive
+    indices: RefCell<HashMap<String, u32>>,
+    /// how many names (from the start) are known to be in the caches
+    scanned: Cell<u32>,
 }
 
 impl<'a> NameManager<'a> {
     pub fn new(process: &'a Process, address: Address) -> NameManager<'a> {
-        NameManager { process, address }
+        NameManager {
+            process,
+            address,
+            names: RefCell::new(HashMap::new()),
+            indices: RefCell::new(HashMap::new()),
+            scanned: Cell::new(0),
+        }
     }
 
     pub fn get_chars(&self, index: u32) -> Result<String, Error> {
-        let read = self.process.read_pointer_path::<ArrayCString<128>>(
-            self.address,
-            asr::PointerSize::Bit64,
-            &[0x8, index as u64 * NAME_ENTRY_SIZE, 0x0],
-        )?;
-
-        match read.validate_utf8() {
-            Ok(s) => Ok(s.to_owned()),
-            Err(e) => {
-                asr::print_message(&format!("what? {e}"));
-                panic!("waaaah..");
-            }
+        if let Some(name) = self.names.borrow().get(&index) {
+            return Ok(name.to_owned());
         }
+
+        let name = self.read_name(index)?;
+        self.remember(index, &name);
+
+        Ok(name)
+    }
+
+    pub fn get(&self, name: FName) -> Result<String, Error> {
+        self.get_chars(name.index())
+    }
+
+    pub fn num_names(&self) -> Result<u32, Error> {
+        self.process.read(self.address + NUM_NAMES)
+    }
+
+    /// The name with this string (ignoring ASCII case, like zdoom does), if there is one. This
+    /// only goes to memory for names that were added since the last time we looked.
+    pub fn find(&self, name: &str) -> Result<Option<FName>, Error> {
+        let name = name.to_ascii_lowercase();
+        if let Some(index) = self.indices.borrow().get(&name) {
+            return Ok(Some(FName(*index)));
+        }
+
+        let num_names = self.num_names()?;
+        for index in self.scanned.get()..num_names {
+            let _ = self.get_chars(index)?;
+            self.scanned.set(index + 1);
+        }
+
+        Ok(self.indices.borrow().get(&name).map(|index| FName(*index)))
+    }
+
+    fn read_name(&self, index: u32) -> Result<String, Error> {
+        let name_array: Address = self.process.read::<u64>(self.address + NAME_ARRAY)?.into();
+        let chars: Address = self
+            .process
+            .read::<u64>(name_array + index as u64 * NAME_ENTRY_SIZE)?
+            .into();
+
+        read_c_string(self.process, chars)
+    }
+
+    fn remember(&self, index: u32, name: &str) {
+        self.names.borrow_mut().insert(index, name.to_owned());
+        // names are unique (ignoring case), but keep the first in case of garbage
+        self.indices
+            .borrow_mut()
+            .entry(name.to_ascii_lowercase())
+            .or_insert(index);
     }
 }
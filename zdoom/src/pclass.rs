@@ -1,27 +1,29 @@
 use std::{cmp::Ordering, collections::HashMap, rc::Rc};
 
-use asr::{string::ArrayCString, Address, Error, Process};
+use asr::{Address, Error, Process};
 use bitflags::bitflags;
+use helpers::warn;
 use once_cell::unsync::OnceCell;
 use regex::Regex;
 
-use super::{name_manager::NameManager, tarray::TArray, Memory};
+use super::{fstring::FString, name_manager::NameManager, tarray::TArray, Memory};
 
-const DOBJECT_CLASS: u64 = 0x8;
+pub(crate) const DOBJECT_CLASS: u64 = 0x8;
 
-const PFIELD_NAME: u64 = 0x28;
-const PFIELD_OFFSET: u64 = 0x38;
+pub(crate) const PFIELD_NAME: u64 = 0x28;
+pub(crate) const PFIELD_OFFSET: u64 = 0x38;
 const PFIELD_TYPE: u64 = 0x40;
 const PFIELD_FLAGS: u64 = 0x48;
 
-const PCLASS_SIZE: u64 = 0x30;
-const PCLASS_TYPENAME: u64 = 0x38;
+const PCLASS_PARENT: u64 = 0x0;
+pub(crate) const PCLASS_SIZE: u64 = 0x30;
+pub(crate) const PCLASS_TYPENAME: u64 = 0x38;
 const PCLASS_PTYPE: u64 = 0x90;
 
 const PTYPE_SIZE: u64 = 0xC;
 const PTYPE_ALIGN: u64 = 0x10;
 const PTYPE_FLAGS: u64 = 0x14;
-const PTYPE_DESCRIPTIVE_NAME: u64 = 0x48;
+pub(crate) const PTYPE_DESCRIPTIVE_NAME: u64 = 0x48;
 
 #[derive(Clone)]
 pub struct PClass<'a> {
@@ -34,8 +36,13 @@ pub struct PClass<'a> {
     name: OnceCell<String>,
     ptype: OnceCell<PType<'a>>,
     fields: OnceCell<HashMap<String, PField<'a>>>, // does not contain fields of superclasses
+    parent: OnceCell<Option<Box<PClass<'a>>>>,
+    all_fields: OnceCell<HashMap<String, PField<'a>>>, // fields including those of superclasses
 }
 
+/// How far up the inheritance chain we go before assuming we're reading garbage.
+const MAX_CLASS_DEPTH: usize = 64;
+
 impl<'a> PClass<'a> {
     pub fn new(
         process: &'a Process,
@@ -53,9 +60,15 @@ impl<'a> PClass<'a> {
             name: OnceCell::new(),
             ptype: OnceCell::new(),
             fields: OnceCell::new(),
+            parent: OnceCell::new(),
+            all_fields: OnceCell::new(),
         }
     }
 
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
     pub fn size(&self) -> Result<&u32, Error> {
         self.size
             .get_or_try_init(|| self.process.read(self.address + PCLASS_SIZE))
@@ -97,26 +110,95 @@ impl<'a> PClass<'a> {
         })
     }
 
+    /// The class this one directly inherits from, or `None` for the root class (`Object`).
+    pub fn parent(&self) -> Result<Option<&PClass<'a>>, Error> {
+        self.parent
+            .get_or_try_init(|| {
+                let parent: Address = self
+                    .process
+                    .read::<u64>(self.address + PCLASS_PARENT)?
+                    .into();
+
+                if parent == Address::NULL {
+                    return Ok(None);
+                }
+
+                Ok(Some(Box::new(PClass::new(
+                    self.process,
+                    self.memory.clone(),
+                    self.name_manager.clone(),
+                    parent,
+                ))))
+            })
+            .map(|parent| parent.as_deref())
+    }
+
+    /// Every class this one inherits from, starting with its parent and ending at the root.
+    pub fn ancestors(&self) -> Result<Vec<&PClass<'a>>, Error> {
+        let mut ancestors = Vec::new();
+
+        let mut class = self.parent()?;
+        while let Some(current) = class {
+            if ancestors.len() >= MAX_CLASS_DEPTH {
+                warn!(
+                    "{} has more than {MAX_CLASS_DEPTH} ancestors, stopping",
+                    self.name()?
+                );
+                break;
+            }
+
+            ancestors.push(current);
+            class = current.parent()?;
+        }
+
+        Ok(ancestors)
+    }
+
+    /// Whether this class is `name`, or inherits from it. Class names are case insensitive, like
+    /// in ZScript.
+    pub fn is_descendant_of(&self, name: &str) -> Result<bool, Error> {
+        if self.name()?.eq_ignore_ascii_case(name) {
+            return Ok(true);
+        }
+
+        for ancestor in self.ancestors()? {
+            if ancestor.name()?.eq_ignore_ascii_case(name) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Every field of this class, including those declared on its superclasses.
+    pub fn all_fields<'b>(&'b self) -> Result<&'b HashMap<String, PField<'a>>, Error> {
+        self.all_fields.get_or_try_init(|| {
+            let mut all_fields = HashMap::new();
+
+            // from the root down, so a field redeclared in a subclass wins
+            for class in self.ancestors()?.into_iter().rev() {
+                all_fields.extend(class.fields()?.clone());
+            }
+            all_fields.extend(self.fields()?.clone());
+
+            Ok(all_fields)
+        })
+    }
+
+    /// Find a field on this class or any of its superclasses.
+    pub fn find_field<'b>(&'b self, name: &str) -> Result<Option<&'b PField<'a>>, Error> {
+        Ok(self.all_fields()?.get(name))
+    }
+
     pub fn show_class(&self) -> Result<String, Error> {
         let mut struct_out = String::new();
 
         let class_size = self.size()?.to_owned();
         struct_out.push_str(&format!("// size: 0x{class_size:X}\n"));
 
-        let parent_class: Address = self
-            .process
-            .read_pointer_path::<u64>(self.address, asr::PointerSize::Bit64, &[0x0])?
-            .into();
-
         struct_out.push_str(&format!("class {} ", self.name()?));
 
-        if parent_class != Address::NULL {
-            let parent_class = PClass::new(
-                self.process,
-                self.memory.clone(),
-                self.name_manager.clone(),
-                parent_class,
-            );
+        if let Some(parent_class) = self.parent()? {
             struct_out.push_str(&format!(": public {} ", parent_class.name()?));
         }
 
@@ -400,20 +482,8 @@ impl<'a> PType<'a> {
     }
 
     pub fn name(&self) -> Result<&String, Error> {
-        self.name.get_or_try_init(|| {
-            let c_str = self.process.read_pointer_path::<ArrayCString<128>>(
-                self.address,
-                asr::PointerSize::Bit64,
-                &[PTYPE_DESCRIPTIVE_NAME, 0x0],
-            );
-
-            let b = c_str?
-                .validate_utf8()
-                .expect("name should always be utf-8")
-                .to_owned();
-
-            Ok(b)
-        })
+        self.name
+            .get_or_try_init(|| FString::read(self.process, self.address + PTYPE_DESCRIPTIVE_NAME))
     }
 
     pub fn name_as_field_type(name: String) -> Result<String, regex::Error> {
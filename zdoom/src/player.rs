@@ -1,8 +1,9 @@
 use std::rc::Rc;
 
 use crate::name_manager::NameManager;
-use asr::{print_message, Address, Error, Process};
+use asr::{Address, Error, Process};
 use bytemuck::CheckedBitPattern;
+use helpers::warn;
 use once_cell::unsync::OnceCell;
 
 use super::{pclass::PClass, Memory};
@@ -137,7 +138,7 @@ impl<'a> Player<'a> {
         })();
 
         if res.is_err() {
-            print_message("Encountered an error while dumping inventories.");
+            warn!("Encountered an error while dumping inventories.");
         }
     }
 }
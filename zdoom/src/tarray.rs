@@ -1,7 +1,29 @@
-use std::marker::PhantomData;
-
 use asr::{Address, Error, Process};
 use bytemuck::CheckedBitPattern;
+use helpers::warn;
+
+use crate::dobject::Value;
+use crate::name_manager::NameManager;
+
+const TARRAY_DATA: u64 = 0x0;
+const TARRAY_COUNT: u64 = 0x8;
+const TARRAY_MOST: u64 = 0xC;
+
+const TMAP_NODES: u64 = 0x0;
+const _TMAP_LAST_FREE: u64 = 0x8;
+const TMAP_SIZE: u64 = 0x10;
+const TMAP_NUM_USED: u64 = 0x14;
+const TMAP_NODE_NEXT: u64 = 0x0;
+const TMAP_NODE_KEY: u64 = 0x8;
+/// `Next` of a node that isn't in use
+const TMAP_NODE_FREE: u64 = 0x1;
+
+/// Anything bigger is assumed to be garbage rather than a real array
+const MAX_BYTES: u64 = 0x100_0000;
+
+fn align_up(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
 
 pub struct TArray<'a> {
     process: &'a Process,
@@ -13,17 +35,49 @@ impl<'a> TArray<'a> {
         TArray { process, address }
     }
 
-    /// Iterate over each item in this TArray, reading the full item each time
-    pub fn iter<T: CheckedBitPattern>(&self) -> Result<TArrayIterator<'a, T>, Error> {
-        let size =
-            self.process
-                .read_pointer_path(self.address, asr::PointerSize::Bit64, &[0x8_u64])?;
-        Ok(TArrayIterator::<T> {
-            _phantom: PhantomData,
-            process: self.process,
-            address: self.address,
-            size,
-            index: 0,
+    /// The address of the elements and how many there are, or `Err(None)` if the header doesn't
+    /// make sense for elements of `item_size`.
+    pub fn header(&self, item_size: u64) -> Result<(Address, u32), Option<Error>> {
+        let data: Address = self.process.read::<u64>(self.address + TARRAY_DATA)?.into();
+        let count: u32 = self.process.read(self.address + TARRAY_COUNT)?;
+        let most: u32 = self.process.read(self.address + TARRAY_MOST)?;
+
+        // not logged, this is also used to check whether something is a TArray at all
+        if count > most || count as u64 * item_size > MAX_BYTES {
+            return Err(None);
+        }
+
+        Ok((data, count))
+    }
+
+    /// Read every item in one go.
+    pub fn read<T: CheckedBitPattern>(&self) -> Result<Vec<T>, Option<Error>> {
+        let item_size = std::mem::size_of::<T>();
+        let (data, count) = self.header(item_size as u64)?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut buf = vec![0; count as usize * item_size];
+        self.process.read_into_buf(data, &mut buf)?;
+
+        buf.chunks_exact(item_size)
+            .map(|item| bytemuck::checked::try_pod_read_unaligned(item).map_err(|_| None))
+            .collect()
+    }
+
+    /// Iterate over each item in this TArray, which are all read up front
+    ///
+    /// An array with a nonsensical header is treated as empty.
+    pub fn iter<T: CheckedBitPattern>(&self) -> Result<TArrayIterator<T>, Error> {
+        let items = match self.read::<T>() {
+            Ok(items) => items,
+            Err(Some(e)) => return Err(e),
+            Err(None) => Vec::new(),
+        };
+
+        Ok(TArrayIterator {
+            items: items.into_iter(),
         })
     }
 
@@ -32,7 +86,32 @@ impl<'a> TArray<'a> {
     /// This is useful when you don't want to read the full content of each item,
     /// or you don't know the exact structure of the item (e.g. it's dependent on zdoom version)
     pub fn iter_addr(&self, item_size: u64) -> Result<TArrayAddressIterator, Error> {
-        TArrayAddressIterator::new(self.process, self.address, item_size)
+        let (array_addr, size) = match self.header(item_size) {
+            Ok(header) => header,
+            Err(Some(e)) => return Err(e),
+            Err(None) => (Address::NULL, 0),
+        };
+
+        Ok(TArrayAddressIterator {
+            item_size,
+            array_addr,
+            size,
+            index: 0,
+        })
+    }
+
+    /// Read every item, decoding them according to their type name (e.g. `SInt4` or
+    /// `Pointer<Objective>`, as in `Value::Array`).
+    pub fn read_values(
+        &self,
+        name_manager: &NameManager,
+        element_type: &str,
+    ) -> Result<Vec<Value>, Option<Error>> {
+        let (size, _) = Value::layout(element_type).ok_or(None)?;
+
+        self.iter_addr(size)?
+            .map(|address| Value::read(self.process, name_manager, element_type, address))
+            .collect()
     }
 }
 
@@ -43,21 +122,6 @@ pub struct TArrayAddressIterator {
     index: u32,
 }
 
-impl TArrayAddressIterator {
-    fn new(
-        process: &Process,
-        address: Address,
-        item_size: u64,
-    ) -> Result<TArrayAddressIterator, Error> {
-        Ok(TArrayAddressIterator {
-            item_size,
-            array_addr: process.read::<u64>(address + 0x0_u64)?.into(),
-            size: process.read(address + 0x8_u64)?,
-            index: 0,
-        })
-    }
-}
-
 impl Iterator for TArrayAddressIterator {
     type Item = Address;
 
@@ -74,30 +138,129 @@ impl Iterator for TArrayAddressIterator {
     }
 }
 
-pub struct TArrayIterator<'a, T: CheckedBitPattern> {
-    _phantom: PhantomData<T>,
-    process: &'a Process,
-    address: Address,
-    size: u32,
-    index: u32,
+pub struct TArrayIterator<T: CheckedBitPattern> {
+    items: std::vec::IntoIter<T>,
 }
 
-impl<'a, T: CheckedBitPattern> Iterator for TArrayIterator<'a, T> {
+impl<T: CheckedBitPattern> Iterator for TArrayIterator<T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.size {
-            return None;
+        self.items.next()
+    }
+}
+
+/// A `TMap`, which is what ZScript's `Map<K, V>` is built on.
+///
+/// The nodes are one block of `Size` entries, each being `{ Next, Key, Value }`. Nodes that
+/// aren't in use have `Next` set to 1.
+pub struct TMap<'a> {
+    process: &'a Process,
+    address: Address,
+}
+
+impl<'a> TMap<'a> {
+    pub fn new(process: &'a Process, address: Address) -> TMap<'a> {
+        TMap { process, address }
+    }
+
+    /// How many entries are in the map.
+    pub fn len(&self) -> Result<u32, Error> {
+        self.process.read(self.address + TMAP_NUM_USED)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// The addresses of the keys and values of the nodes in use, where the value is at
+    /// `value_offset` from the key and each node is `node_size` bytes.
+    fn entry_addrs(
+        &self,
+        value_offset: u64,
+        node_size: u64,
+    ) -> Result<Vec<(Address, Address)>, Option<Error>> {
+        let nodes: Address = self.process.read::<u64>(self.address + TMAP_NODES)?.into();
+        let size: u32 = self.process.read(self.address + TMAP_SIZE)?;
+        let num_used = self.len()?;
+
+        if num_used > size || size as u64 * node_size > MAX_BYTES {
+            warn!(
+                "TMap at 0x{} has a bad header: {num_used}/{size}",
+                self.address
+            );
+            return Err(None);
+        }
+        if nodes == Address::NULL || size == 0 {
+            return Ok(Vec::new());
         }
 
-        let offset = std::mem::size_of::<T>() * self.index as usize;
-        let item = self
-            .process
-            .read_pointer_path(self.address, asr::PointerSize::Bit64, &[0x0, offset as u64])
-            .ok();
+        let mut entries = Vec::with_capacity(num_used as usize);
+        for index in 0..size as u64 {
+            let node = nodes + index * node_size;
+            if self.process.read::<u64>(node + TMAP_NODE_NEXT)? == TMAP_NODE_FREE {
+                continue;
+            }
 
-        self.index += 1;
+            let key = node + TMAP_NODE_KEY;
+            entries.push((key, key + value_offset));
+        }
 
-        item
+        Ok(entries)
     }
+
+    /// Read every entry, for keys and values that can be read directly.
+    pub fn read<K: CheckedBitPattern, V: CheckedBitPattern>(
+        &self,
+    ) -> Result<Vec<(K, V)>, Option<Error>> {
+        let (value_offset, node_size) = node_layout(
+            (
+                std::mem::size_of::<K>() as u64,
+                std::mem::align_of::<K>() as u64,
+            ),
+            (
+                std::mem::size_of::<V>() as u64,
+                std::mem::align_of::<V>() as u64,
+            ),
+        );
+
+        self.entry_addrs(value_offset, node_size)?
+            .into_iter()
+            .map(|(key, value)| Ok((self.process.read(key)?, self.process.read(value)?)))
+            .collect()
+    }
+
+    /// Read every entry, decoding the keys and values according to their type names (as in
+    /// `Value::Map`).
+    pub fn read_values(
+        &self,
+        name_manager: &NameManager,
+        key_type: &str,
+        value_type: &str,
+    ) -> Result<Vec<(Value, Value)>, Option<Error>> {
+        let (value_offset, node_size) = node_layout(
+            Value::layout(key_type).ok_or(None)?,
+            Value::layout(value_type).ok_or(None)?,
+        );
+
+        self.entry_addrs(value_offset, node_size)?
+            .into_iter()
+            .map(|(key, value)| {
+                Ok((
+                    Value::read(self.process, name_manager, key_type, key)?,
+                    Value::read(self.process, name_manager, value_type, value)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Where the value is relative to the key, and the size of a whole node, given the (size, align)
+/// of the key and value.
+fn node_layout((key_size, _): (u64, u64), (value_size, value_align): (u64, u64)) -> (u64, u64) {
+    let value_offset = align_up(key_size, value_align);
+    // the node starts with a pointer, so it's at least pointer aligned
+    let node_size = align_up(TMAP_NODE_KEY + value_offset + value_size, 0x8);
+
+    (value_offset, node_size)
 }
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use asr::{Address, Error, Process};
+use helpers::{info, warn};
+
+use crate::dobject::DObject;
+use crate::pclass::DOBJECT_CLASS;
+use crate::ZDoom;
+
+/// STAT_* goes up to 127, and there's one more list for thinkers that are being destroyed
+pub const NUM_STATS: u64 = 129;
+const MAX_THINKERS_PER_LIST: u32 = 0x10_0000;
+
+/// `FreshThinkers` comes right after `Thinkers`, with one list per stat but not the extra one
+const NUM_FRESH_STATS: u64 = NUM_STATS - 1;
+
+/// Where to look for `FLevelLocals::Thinkers`, the array of `FThinkerList`s (just a sentinel
+/// pointer each).
+const THINKERS_SEARCH_SIZE: u64 = 0x4000;
+
+/// Find `Thinkers` in the level: 129 pointers in a row that are all either null (the list was
+/// never used) or a sentinel, which is a plain `Thinker`, followed by the 128 of `FreshThinkers`.
+///
+/// Leading null fields would fit too, so the lists have to start right after something that isn't
+/// a list, and each list has to lead back to its sentinel through `NextThinker` (at
+/// `next_offset`).
+pub(crate) fn probe_thinkers_offset(
+    process: &Process,
+    level_addr: Address,
+    thinker_class: Address,
+    next_offset: u64,
+) -> Option<u64> {
+    let lists = (NUM_STATS + NUM_FRESH_STATS) as usize;
+    let len = THINKERS_SEARCH_SIZE as usize + lists * 0x8;
+    let mut level = vec![0_u8; len];
+    // the level might not be that big, so read what we can a page at a time
+    let mut readable = 0;
+    for page in level.chunks_mut(0x1000) {
+        if process
+            .read_into_buf(level_addr + readable as u64, page)
+            .is_err()
+        {
+            break;
+        }
+        readable += page.len();
+    }
+
+    let pointers = level[..readable]
+        .chunks_exact(0x8)
+        .map(|p| u64::from_le_bytes(p.try_into().unwrap()))
+        .collect::<Vec<_>>();
+
+    let is_list = pointers
+        .iter()
+        .map(|&pointer| {
+            pointer == 0
+                || (pointer.is_multiple_of(0x8)
+                    && pointer > 0x10000
+                    && process
+                        .read::<u64>(pointer + DOBJECT_CLASS)
+                        .is_ok_and(|class| class == thinker_class.value()))
+        })
+        .collect::<Vec<_>>();
+
+    // an empty list's sentinel points at itself
+    let leads_back = |sentinel: u64| {
+        let mut current = sentinel;
+        for _ in 0..MAX_THINKERS_PER_LIST {
+            match process.read::<u64>(current + next_offset) {
+                Ok(next) if next == sentinel => return true,
+                Ok(next) if next != 0 => current = next,
+                _ => return false,
+            }
+        }
+
+        false
+    };
+
+    let offset = (1..=pointers.len().saturating_sub(lists))
+        .find(|&start| {
+            let thinkers = &pointers[start..start + NUM_STATS as usize];
+            !is_list[start - 1]
+                && is_list[start..start + lists].iter().all(|is_list| *is_list)
+                && thinkers.iter().any(|p| *p != 0)
+                && thinkers.iter().filter(|p| **p != 0).all(|p| leads_back(*p))
+        })
+        .map(|index| index as u64 * 0x8);
+
+    match offset {
+        Some(offset) => info!("zdoom: Probed level_thinkers at 0x{offset:X}"),
+        None => warn!("zdoom: Couldn't find the level's thinkers"),
+    }
+
+    offset
+}
+
+/// Walks the level's thinker lists, giving every thinker (of a known class) as an object.
+///
+/// Each list is circular, starting and ending at its sentinel.
+pub struct Thinkers<'z, 'a> {
+    zdoom: &'z ZDoom<'a>,
+    /// `DThinker::NextThinker`, the first thing after `DObject`'s members
+    next_offset: u64,
+    sentinels: std::vec::IntoIter<Address>,
+    /// only give thinkers whose class is one of these
+    classes: Option<HashSet<Address>>,
+    sentinel: Address,
+    current: Address,
+    steps: u32,
+}
+
+impl<'z, 'a> Thinkers<'z, 'a> {
+    pub(crate) fn new(
+        zdoom: &'z ZDoom<'a>,
+        next_offset: u64,
+        sentinels: Vec<Address>,
+        classes: Option<HashSet<Address>>,
+    ) -> Thinkers<'z, 'a> {
+        Thinkers {
+            zdoom,
+            next_offset,
+            sentinels: sentinels.into_iter(),
+            classes,
+            sentinel: Address::NULL,
+            current: Address::NULL,
+            steps: 0,
+        }
+    }
+
+    fn next_thinker(&self, thinker: Address) -> Result<Address, Error> {
+        Ok(self
+            .zdoom
+            .process
+            .read::<u64>(thinker + self.next_offset)?
+            .into())
+    }
+
+    /// Move on to the next thinker, going to the next list when this one is done.
+    fn advance(&mut self) -> Option<Address> {
+        loop {
+            let next = match self.current {
+                current if current == Address::NULL => None,
+                current => self.next_thinker(current).ok(),
+            };
+
+            self.steps += 1;
+            match next {
+                Some(next)
+                    if next != Address::NULL
+                        && next != self.sentinel
+                        && self.steps < MAX_THINKERS_PER_LIST =>
+                {
+                    self.current = next;
+                    return Some(next);
+                }
+                _ => {
+                    self.sentinel = self.sentinels.next()?;
+                    self.current = self.sentinel;
+                    self.steps = 0;
+                }
+            }
+        }
+    }
+}
+
+impl<'z, 'a> Iterator for Thinkers<'z, 'a> {
+    type Item = DObject<'z, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let thinker = self.advance()?;
+            if let Some(classes) = &self.classes {
+                let class = self.zdoom.process.read::<u64>(thinker + DOBJECT_CLASS);
+                if !class.is_ok_and(|class| classes.contains(&class.into())) {
+                    continue;
+                }
+            }
+
+            // thinkers of classes we don't know are skipped, they're likely being destroyed
+            if let Ok(object) = self.zdoom.object(thinker) {
+                return Some(object);
+            }
+        }
+    }
+}